@@ -1,126 +1,365 @@
+use crate::card::Card;
+use crate::error::Result;
 use crate::hand::Hand;
 use crate::hand_score::display_hand_data;
 use crate::hand_score::HandData;
 use crate::hand_score::HandScore;
-use crate::hand_stats::HandStats;
-use rand::rngs::ThreadRng;
+use crate::hand_statistic::{
+    enumerate_statistic, parallel_sample_statistic, sample_statistic, HandStatistic,
+};
+use crate::hand_value::HandCategory;
 use rand::Rng;
-use rayon::prelude::*;
 use std::fmt;
-use std::sync::Arc;
-use std::sync::Mutex;
 
 pub type AggregateScore = HandData;
 
+/// How hands are counted in an `AggregateScore`.
+#[derive(Default, PartialEq, Eq, Debug, Copy, Clone)]
+pub enum CountingMode {
+    /// Each hand counts in every category it makes, so a full house is also
+    /// a pair and three of a kind, and every hand counts as a high card.
+    #[default]
+    Cumulative,
+    /// Each hand counts only in its best category, so the counts add up to
+    /// the number of hands, as in the usual published tables.
+    Exclusive,
+}
+
 impl AggregateScore {
-    #[allow(dead_code)]
-    pub fn insert(&mut self, score: &HandData) {
-        self.high_card += score.high_card;
-        self.pair += score.pair;
-        self.two_pair += score.two_pair;
-        self.three_of_a_kind += score.three_of_a_kind;
-        self.straight += score.straight;
-        self.flush += score.flush;
-        self.full_house += score.full_house;
-        self.four_of_a_kind += score.four_of_a_kind;
-        self.straight_flush += score.straight_flush;
+    /// Same as `insert`, counting the score in the given mode.
+    pub fn insert_counted(&mut self, score: &HandScore, mode: CountingMode) {
+        self.insert_weighted_counted(score, 1, mode);
     }
-}
 
-impl fmt::Display for AggregateScore {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let scale = if self.high_card > 0 {
-            100.0 / (self.high_card as f64)
+    /// Same as `insert_weighted`, counting the score in the given mode.
+    pub fn insert_weighted_counted(&mut self, score: &HandScore, weight: u64, mode: CountingMode) {
+        match mode {
+            CountingMode::Cumulative => self.insert_weighted(score, weight),
+            CountingMode::Exclusive => self.insert_weighted(&score.best_category_only(), weight),
+        }
+    }
+
+    /// Number of hands that were inserted in the given mode.
+    pub fn num_hands(&self, mode: CountingMode) -> u64 {
+        match mode {
+            CountingMode::Cumulative => self[HandCategory::HighCard],
+            CountingMode::Exclusive => self.iter().map(|(_, count)| count).sum(),
+        }
+    }
+
+    /// Counts and percentages of every category, for scores that were
+    /// inserted in the given mode.
+    pub fn display_counted(&self, mode: CountingMode) -> String {
+        let num_hands = self.num_hands(mode);
+        let scale = if num_hands > 0 {
+            100.0 / (num_hands as f64)
         } else {
             0.0
         };
-        let n_pad_count = self.high_card.to_string().len();
+        let n_pad_count = num_hands.to_string().len();
         let display_member =
             |count| format!("{:<n_pad_count$} ({:>7.3}%)", count, scale * (count as f64));
-        write!(
-            f,
-            "{}",
-            display_hand_data(&self, "HandScore", display_member)
-        )
+        let object_name = match mode {
+            CountingMode::Cumulative => "HandScore",
+            CountingMode::Exclusive => "HandScore (exclusive)",
+        };
+        display_hand_data(self, object_name, display_member)
+    }
+
+    pub fn insert(&mut self, score: &HandData) {
+        for (category, count) in score.iter() {
+            self[category] += count;
+        }
+    }
+
+    /// Same as `insert`, but counts `score` as `weight` separate hands.
+    pub fn insert_weighted(&mut self, score: &HandData, weight: u64) {
+        for (category, count) in score.iter() {
+            self[category] += weight * count;
+        }
+    }
+}
+
+impl fmt::Display for AggregateScore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.display_counted(CountingMode::Cumulative))
+    }
+}
+
+/// The categories of every observed hand, counted in the given mode. This is
+/// how the functions in [`crate::hand_statistic`] collect an
+/// `AggregateScore`.
+#[derive(Default, PartialEq, Debug, Clone)]
+pub struct CountedScores {
+    pub mode: CountingMode,
+    pub scores: AggregateScore,
+}
+
+impl CountedScores {
+    pub fn new(mode: CountingMode) -> CountedScores {
+        CountedScores {
+            mode,
+            scores: AggregateScore::default(),
+        }
+    }
+}
+
+impl<const N: usize> HandStatistic<N> for CountedScores {
+    fn observe(&mut self, hand: &Hand<N>) {
+        self.observe_weighted(hand, 1);
+    }
+
+    fn observe_weighted(&mut self, hand: &Hand<N>, weight: u64) {
+        self.scores
+            .insert_weighted_counted(&HandScore::from(hand), weight, self.mode);
+    }
+
+    fn merge(&mut self, other: &CountedScores) {
+        self.scores.insert(&other.scores);
     }
 }
 
-#[allow(dead_code)]
 pub fn sample_aggregate_scores<const N_HAND: usize, R: Rng>(
     rng: &mut R,
     num_samples: u32,
-) -> AggregateScore {
-    let mut scores = AggregateScore::default();
-    for _ in 0..num_samples {
-        scores.insert(&HandScore::from(&HandStats::from(&Hand::<N_HAND>::draw(
-            rng,
-        ))));
-    }
-    scores
+    num_decks: usize,
+    mode: CountingMode,
+) -> Result<AggregateScore> {
+    sample_aggregate_scores_with_known::<N_HAND, _>(rng, &[], num_samples, num_decks, mode)
 }
 
 pub fn parallel_sample_aggregate_scores<const N_HAND: usize>(
     num_samples: u32,
     num_threads: u32,
-) -> AggregateScore {
-    let scores = Arc::new(Mutex::new(AggregateScore::default()));
-    let num_samples_per_thread = num_samples / num_threads;
-    let num_samples_remainder = num_samples % num_threads;
-    let mut sample_sizes = vec![num_samples_per_thread; num_threads as usize];
-    if num_samples_remainder > 0 {
-        sample_sizes.insert(num_threads as usize, num_samples_remainder)
-    }
-
-    (0..sample_sizes.len()).into_par_iter().for_each(|x| {
-        let scores_temp =
-            sample_aggregate_scores::<N_HAND, ThreadRng>(&mut rand::thread_rng(), sample_sizes[x]);
-        // Creates clone of scores for handling the change
-        let scores = Arc::clone(&scores);
-        // Locks score for thread to enter data. Keeps it locked till it is completed with writing process.
-        let mut scores_clone = scores.lock().unwrap();
-        // Finally adds the data
-        scores_clone.insert(&scores_temp);
-    });
-
-    return Arc::into_inner(scores).unwrap().into_inner().unwrap();
+    num_decks: usize,
+    mode: CountingMode,
+    seed: u64,
+) -> Result<AggregateScore> {
+    parallel_sample_aggregate_scores_with_known::<N_HAND>(
+        &[],
+        num_samples,
+        num_threads,
+        num_decks,
+        mode,
+        seed,
+    )
 }
 
+/// Exact version of `sample_aggregate_scores`: scores every hand that can be
+/// dealt from a shoe of `num_decks` decks, weighted by how many ways it can
+/// be dealt. Practical for five card hands, but slow for seven.
+pub fn enumerate_aggregate_scores<const N_HAND: usize>(
+    num_decks: usize,
+    mode: CountingMode,
+) -> Result<AggregateScore> {
+    enumerate_aggregate_scores_with_known::<N_HAND>(&[], num_decks, mode)
+}
+
+/// Same as `sample_aggregate_scores`, for hands that always include the
+/// `known` cards. The rest of each hand is drawn from the shoe without the
+/// known cards. Fails if the known cards don't fit in the shoe or the hand.
+pub fn sample_aggregate_scores_with_known<const N_HAND: usize, R: Rng>(
+    rng: &mut R,
+    known: &[Card],
+    num_samples: u32,
+    num_decks: usize,
+    mode: CountingMode,
+) -> Result<AggregateScore> {
+    let counted = sample_statistic::<N_HAND, _, _>(
+        rng,
+        &CountedScores::new(mode),
+        known,
+        num_samples,
+        num_decks,
+    )?;
+    Ok(counted.scores)
+}
+
+pub fn parallel_sample_aggregate_scores_with_known<const N_HAND: usize>(
+    known: &[Card],
+    num_samples: u32,
+    num_threads: u32,
+    num_decks: usize,
+    mode: CountingMode,
+    seed: u64,
+) -> Result<AggregateScore> {
+    let counted = parallel_sample_statistic::<N_HAND, _>(
+        &CountedScores::new(mode),
+        known,
+        num_samples,
+        num_threads,
+        num_decks,
+        seed,
+    )?;
+    Ok(counted.scores)
+}
+
+/// Exact version of `sample_aggregate_scores_with_known`, which scores every
+/// way to complete the hand. Seven card hands with two known cards deal about
+/// two million completions from a single deck.
+pub fn enumerate_aggregate_scores_with_known<const N_HAND: usize>(
+    known: &[Card],
+    num_decks: usize,
+    mode: CountingMode,
+) -> Result<AggregateScore> {
+    let counted = enumerate_statistic::<N_HAND, _>(&CountedScores::new(mode), known, num_decks)?;
+    Ok(counted.scores)
+}
+
+//////////////////////////////////////////////////////////////////////////////////////
+
 #[cfg(test)]
 mod tests {
+    use crate::aggregate_score::enumerate_aggregate_scores;
+    use crate::aggregate_score::enumerate_aggregate_scores_with_known;
+    use crate::aggregate_score::parallel_sample_aggregate_scores;
+    use crate::aggregate_score::parallel_sample_aggregate_scores_with_known;
+    use crate::aggregate_score::sample_aggregate_scores;
+    use crate::aggregate_score::sample_aggregate_scores_with_known;
     use crate::aggregate_score::AggregateScore;
+    use crate::aggregate_score::CountingMode;
+    use crate::deck::Deck;
+    use crate::error::PokerError;
     use crate::hand_score::HandScore;
+    use crate::hand_value::HandCategory;
+    use rand::SeedableRng;
 
     #[test]
     fn basic_operation() {
         let mut scores = AggregateScore::default();
 
-        scores.insert(&HandScore {
-            high_card: 1,
-            pair: 1,
-            two_pair: 1,
-            ..Default::default()
-        });
-
-        scores.insert(&HandScore {
-            high_card: 1,
-            pair: 1,
-            three_of_a_kind: 1,
-            full_house: 1,
-            ..Default::default()
-        });
-
-        assert_eq!(
-            scores,
-            AggregateScore {
-                high_card: 2,
-                pair: 2,
-                two_pair: 1,
-                three_of_a_kind: 1,
-                full_house: 1,
-                ..Default::default()
-            }
-        );
+        scores.insert(&HandScore::from_categories(&[
+            HandCategory::HighCard,
+            HandCategory::Pair,
+            HandCategory::TwoPair,
+        ]));
+
+        scores.insert(&HandScore::from_categories(&[
+            HandCategory::HighCard,
+            HandCategory::Pair,
+            HandCategory::ThreeOfAKind,
+            HandCategory::FullHouse,
+        ]));
+
+        let mut expected = AggregateScore::from_categories(&[
+            HandCategory::HighCard,
+            HandCategory::Pair,
+            HandCategory::TwoPair,
+            HandCategory::ThreeOfAKind,
+            HandCategory::FullHouse,
+        ]);
+        expected[HandCategory::HighCard] = 2;
+        expected[HandCategory::Pair] = 2;
+        assert_eq!(scores, expected);
 
         println!("{scores}")
     }
+
+    /// Exact counts for three card hands, which are small enough to check
+    /// by hand: C(52, 3) = 22100 hands, 13 * C(4, 3) = 52 of them are trips.
+    #[test]
+    fn enumerate_three_card_hands() {
+        let scores = enumerate_aggregate_scores::<3>(1, CountingMode::Cumulative).unwrap();
+        assert_eq!(scores[HandCategory::HighCard], 22100);
+        assert_eq!(scores[HandCategory::ThreeOfAKind], 52);
+        assert_eq!(scores[HandCategory::Pair], 52 + 13 * 6 * 48);
+        assert_eq!(scores[HandCategory::FiveOfAKind], 0);
+
+        // Two decks: C(104, 3) hands, 13 * C(8, 3) = 728 of them are trips.
+        let scores = enumerate_aggregate_scores::<3>(2, CountingMode::Cumulative).unwrap();
+        assert_eq!(scores[HandCategory::HighCard], 182104);
+        assert_eq!(scores[HandCategory::ThreeOfAKind], 728);
+    }
+
+    /// Exclusive counts of every five card hand match the published table.
+    #[test]
+    fn exclusive_five_card_hands() {
+        let scores = enumerate_aggregate_scores::<5>(1, CountingMode::Exclusive).unwrap();
+        let expected = [
+            1302540, 1098240, 123552, 54912, 10200, 5108, 3744, 624, 40, 0, 0,
+        ];
+        for ((category, count), expected) in scores.iter().zip(expected) {
+            assert_eq!(count, expected, "{category:?}");
+        }
+        assert_eq!(scores.num_hands(CountingMode::Exclusive), 2598960);
+        assert!(scores
+            .display_counted(CountingMode::Exclusive)
+            .contains("pair              1098240 ( 42.257%)"));
+    }
+
+    #[test]
+    fn sampled_exclusive_counts() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(31);
+        let scores = sample_aggregate_scores::<5, _>(&mut rng, 1000, 1, CountingMode::Exclusive);
+        assert_eq!(scores.unwrap().num_hands(CountingMode::Exclusive), 1000);
+        assert!(sample_aggregate_scores::<5, _>(&mut rng, 10, 0, CountingMode::Exclusive).is_err());
+        let scores =
+            parallel_sample_aggregate_scores::<5>(1001, 4, 1, CountingMode::Cumulative, 31);
+        assert_eq!(scores.unwrap()[HandCategory::HighCard], 1001);
+    }
+
+    /// Five card hands that start with a pair of aces: C(50, 3) = 19600 ways to
+    /// deal the other three cards.
+    #[test]
+    fn known_cards() {
+        let known = Deck::new().draw_cards(&["A♠", "A♥"]).unwrap();
+        let mode = CountingMode::Cumulative;
+        let scores = enumerate_aggregate_scores_with_known::<5>(&known, 1, mode).unwrap();
+        assert_eq!(scores[HandCategory::HighCard], 19600);
+        assert_eq!(scores[HandCategory::Pair], 19600);
+        // One more ace, both of them, or trips of another rank.
+        assert_eq!(scores[HandCategory::ThreeOfAKind], 2 * 1128 + 48 + 12 * 4);
+        assert_eq!(scores[HandCategory::FourOfAKind], 48);
+        assert_eq!(scores[HandCategory::Flush], 0);
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(99);
+        let sampled =
+            sample_aggregate_scores_with_known::<5, _>(&mut rng, &known, 19600, 1, mode).unwrap();
+        assert_eq!(sampled[HandCategory::Pair], 19600);
+        let two_pair = scores[HandCategory::TwoPair] as f64;
+        assert!((sampled[HandCategory::TwoPair] as f64 - two_pair).abs() < 0.1 * two_pair);
+        let sampled =
+            parallel_sample_aggregate_scores_with_known::<5>(&known, 1001, 4, 1, mode, 99).unwrap();
+        assert_eq!(sampled[HandCategory::Pair], 1001);
+
+        // Both aces of spades from two decks leave C(102, 3) ways to deal the
+        // rest, and C(6, 3) of them make five of a kind with the other aces.
+        let pair = [known[0], known[0]];
+        let scores = enumerate_aggregate_scores_with_known::<5>(&pair, 2, mode).unwrap();
+        assert_eq!(scores[HandCategory::HighCard], 171700);
+        assert_eq!(scores[HandCategory::FiveOfAKind], 20);
+    }
+
+    #[test]
+    fn invalid_known_cards() {
+        let known = Deck::new().draw_cards(&["A♠", "K♠", "Q♠"]).unwrap();
+        let mode = CountingMode::Cumulative;
+        assert!(matches!(
+            enumerate_aggregate_scores_with_known::<2>(&known, 1, mode),
+            Err(PokerError::InvalidCardCount { found: 3, .. })
+        ));
+        let mut rng = rand::rngs::StdRng::seed_from_u64(5);
+        assert!(matches!(
+            sample_aggregate_scores_with_known::<2, _>(&mut rng, &known, 10, 1, mode),
+            Err(PokerError::InvalidCardCount { found: 3, .. })
+        ));
+        // The same card twice in a single deck.
+        let pair = [known[0], known[0]];
+        assert!(matches!(
+            enumerate_aggregate_scores_with_known::<5>(&pair, 1, mode),
+            Err(PokerError::DuplicateCards)
+        ));
+        assert!(matches!(
+            sample_aggregate_scores_with_known::<5, _>(&mut rng, &pair, 10, 1, mode),
+            Err(PokerError::DuplicateCards)
+        ));
+        assert!(matches!(
+            parallel_sample_aggregate_scores_with_known::<5>(&pair, 10, 2, 1, mode, 5),
+            Err(PokerError::DuplicateCards)
+        ));
+        assert!(matches!(
+            enumerate_aggregate_scores_with_known::<5>(&known, 0, mode),
+            Err(PokerError::Zero("decks"))
+        ));
+    }
 }
@@ -1,4 +1,4 @@
-use clap::{Args, Parser, Subcommand};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 
 #[derive(Debug, Parser)]
 #[clap(author, version, about)]
@@ -12,11 +12,117 @@ pub enum CommandsEnum {
     /// Draw random hand, display, and score
     DrawHand { hands_size: usize },
 
+    /// Describe the best poker hand that can be made from some cards
+    Score {
+        /// Cards to score, for example: Ah Jh 9h 6h 2h Ks 3c
+        #[arg(num_args = 1.., required = true)]
+        cards: Vec<String>,
+    },
+
     /// Compute statistics on a 5/7 card hand with N samples
     Statistics(StatisticsSampleParameters),
 
     /// To print sorted deck
     SortedDeck,
+
+    /// Compare discard strategies for five card draw with N samples
+    DrawPoker {
+        number_of_samples: u32,
+
+        number_of_threads: u32,
+    },
+
+    /// Return to player of a video poker machine, or the best hold for one deal
+    VideoPoker {
+        #[arg(value_enum)]
+        game: VideoPokerGame,
+
+        /// Five dealt cards to analyze, for example: --hand Qd 4c Qh 8s 2h
+        #[arg(long, num_args = 5)]
+        hand: Option<Vec<String>>,
+    },
+
+    /// Cards that improve a hold'em hand on the flop or turn
+    Outs {
+        /// Hole cards, for example: Ah Kh
+        #[arg(num_args = 2, required = true)]
+        hole_cards: Vec<String>,
+
+        /// The flop or turn, for example: --board 2h 7h 9c
+        #[arg(long, num_args = 3..=4, required = true)]
+        board: Vec<String>,
+
+        /// Hole cards of an opponent, which can be repeated for each opponent
+        #[arg(long, num_args = 2)]
+        opponent: Vec<String>,
+    },
+
+    /// Chance of making each category by the flop, turn and river in hold'em
+    StreetOdds {
+        /// Hole cards, for example: Ah Kh
+        #[arg(num_args = 2, required = true)]
+        hole_cards: Vec<String>,
+
+        /// Community cards dealt so far, for example: --board 2h 7h 9c
+        #[arg(long, num_args = 3..=5)]
+        board: Vec<String>,
+
+        /// Sample this many boards instead of dealing every one
+        #[arg(long)]
+        samples: Option<u32>,
+    },
+
+    /// Best possible hold'em hands on a board, and the hole cards that make them
+    Nuts {
+        /// The flop, turn or river, for example: Kh Qh 7h 7c
+        #[arg(num_args = 3..=5, required = true)]
+        board: Vec<String>,
+
+        /// Number of hands to list, from the nuts down
+        #[arg(long, default_value_t = 3)]
+        count: usize,
+    },
+
+    /// Compute preflop equities for all 169 starting hands, and save them
+    PreflopTable {
+        /// Number of samples for each equity in the table
+        number_of_samples: u32,
+
+        /// File to write the table to
+        file: String,
+    },
+
+    /// Sample river equity histograms for all 169 starting hands, and save them as CSV
+    EquityHistograms {
+        /// Number of random boards for each starting hand
+        number_of_boards: u32,
+
+        /// CSV file to write the histograms to
+        file: String,
+
+        /// Number of equity bins in each histogram
+        #[arg(long, default_value_t = 50)]
+        bins: usize,
+    },
+
+    /// Look up the preflop equity of a starting hand in a saved table
+    Preflop {
+        /// File written by the preflop-table command
+        file: String,
+
+        /// Starting hand, for example: AKs, T9o or 77
+        hand: String,
+
+        /// Starting hand of a single opponent, for example: --versus QQ
+        #[arg(long)]
+        versus: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum VideoPokerGame {
+    JacksOrBetter,
+    DeucesWild,
 }
 
 #[derive(Debug, Args)]
@@ -27,5 +133,12 @@ pub struct StatisticsSampleParameters {
     pub number_of_samples: u32,
 
     pub number_of_threads: u32,
-}
 
+    /// Number of standard decks shuffled together in the shoe
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=8))]
+    pub decks: u8,
+
+    /// Count each hand only in its best category, so the percentages add up to 100%
+    #[arg(long)]
+    pub exclusive: bool,
+}
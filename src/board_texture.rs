@@ -0,0 +1,303 @@
+use crate::card::Rank;
+use crate::hand_stats::HandStats;
+use crate::suit_isomorphism::canonical_hands;
+use std::fmt;
+
+/// Number of ranks from the ace as low card to the ace as high card.
+const STRAIGHT_RANKS: usize = Rank::NUM_RANKS + 1;
+const STRAIGHT_LENGTH: usize = 5;
+/// Lowest rank, counting up from the ace as low card, that makes a board
+/// high: the ten.
+const HIGH_CARD_INDEX: usize = 9;
+
+#[derive(PartialEq, Eq, Debug, Copy, Clone, Hash)]
+pub enum SuitTexture {
+    /// No two cards share a suit.
+    Rainbow,
+    /// At most two cards of a suit, so a flush needs two more.
+    TwoTone,
+    /// Three or more cards of a suit, so a flush is possible.
+    FlushPossible,
+    /// Every card has the same suit.
+    Monotone,
+}
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Copy, Clone, Hash)]
+pub enum Pairing {
+    Unpaired,
+    Paired,
+    TwoPair,
+    Trips,
+    FullHouse,
+    Quads,
+}
+
+/// Texture of the community cards on the flop, turn or river.
+#[derive(PartialEq, Eq, Debug, Copy, Clone, Hash)]
+pub struct BoardTexture {
+    pub suits: SuitTexture,
+    pub pairing: Pairing,
+    /// Some straight can be made with two hole cards.
+    pub connected: bool,
+    /// The highest card is a ten or better.
+    pub high: bool,
+    /// Two or more cards share a suit, or the board is connected, so later
+    /// cards can easily change which hands are best. Otherwise it is static.
+    pub dynamic: bool,
+}
+
+impl SuitTexture {
+    /// One of: {Rainbow, Two tone, Flush possible, Monotone}
+    pub fn name(&self) -> &'static str {
+        match self {
+            SuitTexture::Rainbow => "Rainbow",
+            SuitTexture::TwoTone => "Two tone",
+            SuitTexture::FlushPossible => "Flush possible",
+            SuitTexture::Monotone => "Monotone",
+        }
+    }
+
+    fn from_stats(hand_stats: &HandStats) -> SuitTexture {
+        let max_suit = *hand_stats.suit_count.iter().max().unwrap();
+        match max_suit {
+            0 | 1 => SuitTexture::Rainbow,
+            2 => SuitTexture::TwoTone,
+            _ if max_suit == hand_stats.count_cards() => SuitTexture::Monotone,
+            _ => SuitTexture::FlushPossible,
+        }
+    }
+}
+
+impl Pairing {
+    /// One of: {Unpaired, Paired, Two pair, Trips, Full house, Quads}
+    pub fn name(&self) -> &'static str {
+        match self {
+            Pairing::Unpaired => "Unpaired",
+            Pairing::Paired => "Paired",
+            Pairing::TwoPair => "Two pair",
+            Pairing::Trips => "Trips",
+            Pairing::FullHouse => "Full house",
+            Pairing::Quads => "Quads",
+        }
+    }
+
+    fn from_stats(hand_stats: &HandStats) -> Pairing {
+        let groups = |size: usize| {
+            hand_stats
+                .rank_count
+                .iter()
+                .filter(|count| **count >= size)
+                .count()
+        };
+        match (groups(4), groups(3), groups(2)) {
+            (1.., _, _) => Pairing::Quads,
+            (_, 1, 2..) | (_, 2.., _) => Pairing::FullHouse,
+            (_, 1, _) => Pairing::Trips,
+            (_, _, 2..) => Pairing::TwoPair,
+            (_, _, 1) => Pairing::Paired,
+            _ => Pairing::Unpaired,
+        }
+    }
+}
+
+impl From<&HandStats> for BoardTexture {
+    fn from(hand_stats: &HandStats) -> BoardTexture {
+        // Ranks from ace low up to ace high, so the ace counts at both ends.
+        let present: [bool; STRAIGHT_RANKS] =
+            array_init::array_init(|i| hand_stats.rank_count[i % Rank::NUM_RANKS] > 0);
+        let most_in_straight = present
+            .windows(STRAIGHT_LENGTH)
+            .map(|window| window.iter().filter(|present| **present).count())
+            .max()
+            .unwrap();
+        let suits = SuitTexture::from_stats(hand_stats);
+        let connected = most_in_straight >= 3;
+        BoardTexture {
+            suits,
+            pairing: Pairing::from_stats(hand_stats),
+            connected,
+            high: (HIGH_CARD_INDEX..STRAIGHT_RANKS).any(|i| present[i]),
+            dynamic: suits != SuitTexture::Rainbow || connected,
+        }
+    }
+}
+
+impl fmt::Display for BoardTexture {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}, {}, {}, {}, {}",
+            self.suits.name(),
+            self.pairing.name(),
+            if self.connected {
+                "connected"
+            } else {
+                "disconnected"
+            },
+            if self.high { "high" } else { "low" },
+            if self.dynamic { "dynamic" } else { "static" }
+        )
+    }
+}
+
+/// How often each texture comes up, counted over some boards.
+#[derive(Default, PartialEq, Debug, Clone)]
+pub struct TextureFrequencies {
+    pub num_boards: u64,
+    pub rainbow: u64,
+    pub two_tone: u64,
+    pub flush_possible: u64,
+    pub monotone: u64,
+    pub paired: u64,
+    pub trips: u64,
+    pub connected: u64,
+    pub high: u64,
+    pub dynamic: u64,
+}
+
+impl TextureFrequencies {
+    /// Counts a texture `weight` times.
+    pub fn insert(&mut self, texture: &BoardTexture, weight: u64) {
+        self.num_boards += weight;
+        let suits = match texture.suits {
+            SuitTexture::Rainbow => &mut self.rainbow,
+            SuitTexture::TwoTone => &mut self.two_tone,
+            SuitTexture::FlushPossible => &mut self.flush_possible,
+            SuitTexture::Monotone => &mut self.monotone,
+        };
+        *suits += weight;
+        if texture.pairing != Pairing::Unpaired {
+            self.paired += weight;
+        }
+        if texture.pairing >= Pairing::Trips {
+            self.trips += weight;
+        }
+        for (flag, count) in [
+            (texture.connected, &mut self.connected),
+            (texture.high, &mut self.high),
+            (texture.dynamic, &mut self.dynamic),
+        ] {
+            if flag {
+                *count += weight;
+            }
+        }
+    }
+
+    pub fn named_values(&self) -> [(&'static str, u64); 9] {
+        [
+            (SuitTexture::Rainbow.name(), self.rainbow),
+            (SuitTexture::TwoTone.name(), self.two_tone),
+            (SuitTexture::FlushPossible.name(), self.flush_possible),
+            (SuitTexture::Monotone.name(), self.monotone),
+            (Pairing::Paired.name(), self.paired),
+            ("Trips or better", self.trips),
+            ("Connected", self.connected),
+            ("High card", self.high),
+            ("Dynamic", self.dynamic),
+        ]
+    }
+}
+
+impl fmt::Display for TextureFrequencies {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Textures of {} boards:", self.num_boards)?;
+        for (name, count) in self.named_values() {
+            write!(
+                f,
+                "\n  {name:<16} {count:>7} {:>8.3}%",
+                100.0 * count as f64 / self.num_boards as f64
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Texture frequencies over every possible flop, found by classifying each
+/// of the 1,755 flops that differ by more than their suits.
+pub fn flop_texture_frequencies() -> TextureFrequencies {
+    let mut frequencies = TextureFrequencies::default();
+    for (flop, weight) in canonical_hands::<3>(&[]) {
+        frequencies.insert(&BoardTexture::from(&HandStats::from(&flop)), weight);
+    }
+    frequencies
+}
+
+//////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use crate::board_texture::flop_texture_frequencies;
+    use crate::board_texture::BoardTexture;
+    use crate::board_texture::Pairing;
+    use crate::board_texture::SuitTexture;
+    use crate::deck::Deck;
+    use crate::hand_stats::HandStats;
+
+    fn texture(names: &[&str]) -> BoardTexture {
+        BoardTexture::from(&HandStats::from_cards(
+            &Deck::new().draw_cards(names).unwrap(),
+        ))
+    }
+
+    #[test]
+    fn classify_boards() {
+        let dry = texture(&["K♠", "7♦", "2♣"]);
+        assert_eq!(dry.suits, SuitTexture::Rainbow);
+        assert_eq!(dry.pairing, Pairing::Unpaired);
+        assert!(!dry.connected && dry.high && !dry.dynamic);
+        assert_eq!(
+            dry.to_string(),
+            "Rainbow, Unpaired, disconnected, high, static"
+        );
+
+        let wet = texture(&["9♥", "8♥", "7♣"]);
+        assert_eq!(wet.suits, SuitTexture::TwoTone);
+        assert!(wet.connected && !wet.high && wet.dynamic);
+        assert_eq!(
+            wet.to_string(),
+            "Two tone, Unpaired, connected, low, dynamic"
+        );
+
+        // The ace plays low for the wheel.
+        assert!(texture(&["A♠", "2♦", "4♣"]).connected);
+        assert!(texture(&["A♠", "Q♦", "T♣"]).connected);
+        assert!(!texture(&["A♠", "6♦", "9♣"]).connected);
+
+        assert_eq!(texture(&["5♥", "9♥", "J♥"]).suits, SuitTexture::Monotone);
+        let turn = texture(&["5♥", "9♥", "J♥", "5♠"]);
+        assert_eq!(turn.suits, SuitTexture::FlushPossible);
+        assert_eq!(turn.pairing, Pairing::Paired);
+        assert_eq!(texture(&["5♥", "5♦", "J♥", "5♠"]).pairing, Pairing::Trips);
+        assert_eq!(
+            texture(&["5♥", "5♦", "J♥", "J♠", "2♣"]).pairing,
+            Pairing::TwoPair
+        );
+        assert_eq!(
+            texture(&["5♥", "5♦", "J♥", "J♠", "5♣"]).pairing,
+            Pairing::FullHouse
+        );
+        assert!(turn.to_string().starts_with("Flush possible, Paired, "));
+    }
+
+    /// Suit and pairing frequencies of the flop can be counted directly.
+    #[test]
+    fn flop_frequencies() {
+        let frequencies = flop_texture_frequencies();
+        assert_eq!(frequencies.num_boards, 22100);
+        // 13 cards of one suit choose 3, for each of the 4 suits.
+        assert_eq!(frequencies.monotone, 4 * 286);
+        // Three different suits, with one of 13 ranks in each.
+        assert_eq!(frequencies.rainbow, 4 * 13 * 13 * 13);
+        assert_eq!(frequencies.two_tone, 22100 - 4 * 286 - 4 * 2197);
+        assert_eq!(frequencies.flush_possible, 0);
+        // Choose the paired rank and two of its cards, then another card.
+        assert_eq!(frequencies.paired, 13 * 6 * 48 + 13 * 4);
+        assert_eq!(frequencies.trips, 13 * 4);
+        // Every flop except those with all 32 cards from the deuce to the nine.
+        assert_eq!(frequencies.high, 22100 - 32 * 31 * 30 / 6);
+        assert!(frequencies.dynamic > frequencies.connected);
+        assert!(frequencies
+            .to_string()
+            .contains("\n  Monotone            1144    5.176%"));
+    }
+}
@@ -1,99 +1,335 @@
 use rand::Rng;
+use std::cmp::Ordering;
 use std::fmt;
+use std::str::FromStr;
 
 //////////////////////////////////////////////////////////////////////////////////////
 
-#[derive(PartialEq, Eq, PartialOrd, Debug, Copy, Clone, Hash)]
-pub struct Suit {
-    pub id: usize,
+/// The four suits, in the order used for card ids. This is also the order
+/// used to break ties between cards of the same rank.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Copy, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(u8)]
+pub enum Suit {
+    Clubs,
+    Diamonds,
+    Hearts,
+    Spades,
 }
 
-#[derive(PartialEq, Eq, PartialOrd, Debug, Copy, Clone, Hash)]
-pub struct Rank {
-    pub id: usize,
+/// The thirteen ranks, in the order used for card ids: the ace comes first.
+/// Comparisons treat the ace as the highest rank, see [`Rank::cmp_with`] for
+/// ace-low games.
+#[derive(PartialEq, Eq, Debug, Copy, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(u8)]
+pub enum Rank {
+    Ace,
+    Two,
+    Three,
+    Four,
+    Five,
+    Six,
+    Seven,
+    Eight,
+    Nine,
+    Ten,
+    Jack,
+    Queen,
+    King,
 }
 
-#[derive(PartialEq, Eq, PartialOrd, Debug, Copy, Clone, Hash)]
+/// A card stored in a single byte as `rank * 4 + suit`, from 0 (A♣) to 51
+/// (K♠). The id is private, so every `Card` is a valid one. Cards are
+/// ordered by rank with the ace high, then by suit.
+#[derive(PartialEq, Eq, Debug, Copy, Clone, Hash)]
 pub struct Card {
-    pub id: usize,
+    id: u8,
 }
 
+/// Whether the ace ranks above the king or below the two.
+#[derive(PartialEq, Eq, Debug, Copy, Clone, Hash, Default)]
+pub enum AceOrder {
+    #[default]
+    High,
+    Low,
+}
+
+/// Error for a number that is not the index of a rank, suit or card.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub struct OutOfRange(pub u8);
+
+impl fmt::Display for OutOfRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} is out of range", self.0)
+    }
+}
+
+impl std::error::Error for OutOfRange {}
+
+/// Error for a string that is not the name of a card.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct ParseCardError(pub String);
+
+impl fmt::Display for ParseCardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid card name: {:?}", self.0)
+    }
+}
+
+impl std::error::Error for ParseCardError {}
+
 //////////////////////////////////////////////////////////////////////////////////////
 impl Suit {
     pub const NUM_SUITS: usize = 4;
+
+    pub const ALL: [Suit; Suit::NUM_SUITS] =
+        [Suit::Clubs, Suit::Diamonds, Suit::Hearts, Suit::Spades];
+
+    pub fn iter() -> impl DoubleEndedIterator<Item = Suit> + ExactSizeIterator {
+        Suit::ALL.into_iter()
+    }
+
+    /// Position of the suit in `ALL`, from 0 to 3.
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+
+    /// One of: {c, d, h, s}
+    pub fn ascii_name(&self) -> char {
+        match self {
+            Suit::Clubs => 'c',
+            Suit::Diamonds => 'd',
+            Suit::Hearts => 'h',
+            Suit::Spades => 's',
+        }
+    }
 }
 
-/// One of:  {♥, ♣, ♦, ♠, ?}
+impl TryFrom<u8> for Suit {
+    type Error = OutOfRange;
+
+    fn try_from(index: u8) -> Result<Suit, OutOfRange> {
+        Suit::ALL
+            .get(index as usize)
+            .copied()
+            .ok_or(OutOfRange(index))
+    }
+}
+
+/// One of:  {♣, ♦, ♥, ♠}
 impl fmt::Display for Suit {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
             "{}",
-            match self.id {
-                0 => String::from("♣"),
-                1 => String::from("♦"),
-                2 => String::from("♥"),
-                3 => String::from("♠"),
-                _ => String::from("?"),
+            match self {
+                Suit::Clubs => "♣",
+                Suit::Diamonds => "♦",
+                Suit::Hearts => "♥",
+                Suit::Spades => "♠",
             }
         )
     }
 }
 
 impl Rank {
-    #[allow(dead_code)]
     pub const NUM_RANKS: usize = 13;
+
+    pub const ALL: [Rank; Rank::NUM_RANKS] = [
+        Rank::Ace,
+        Rank::Two,
+        Rank::Three,
+        Rank::Four,
+        Rank::Five,
+        Rank::Six,
+        Rank::Seven,
+        Rank::Eight,
+        Rank::Nine,
+        Rank::Ten,
+        Rank::Jack,
+        Rank::Queen,
+        Rank::King,
+    ];
+
+    pub fn iter() -> impl DoubleEndedIterator<Item = Rank> + ExactSizeIterator {
+        Rank::ALL.into_iter()
+    }
+
+    /// Position of the rank in `ALL`, from 0 (ace) to 12 (king).
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+
+    /// Position of the rank from the lowest to the highest, from 0 to 12,
+    /// with the ace at the top or the bottom.
+    pub fn strength(&self, aces: AceOrder) -> usize {
+        match aces {
+            AceOrder::High => (self.index() + Rank::NUM_RANKS - 1) % Rank::NUM_RANKS,
+            AceOrder::Low => self.index(),
+        }
+    }
+
+    /// Compares two ranks with the ace at the top or the bottom. The `Ord`
+    /// implementation is the same as `cmp_with(other, AceOrder::High)`.
+    pub fn cmp_with(&self, other: &Rank, aces: AceOrder) -> Ordering {
+        self.strength(aces).cmp(&other.strength(aces))
+    }
+
+    /// One of: {Ace, Two, Three, ... Queen, King}
+    pub fn name(&self) -> &'static str {
+        match self {
+            Rank::Ace => "Ace",
+            Rank::Two => "Two",
+            Rank::Three => "Three",
+            Rank::Four => "Four",
+            Rank::Five => "Five",
+            Rank::Six => "Six",
+            Rank::Seven => "Seven",
+            Rank::Eight => "Eight",
+            Rank::Nine => "Nine",
+            Rank::Ten => "Ten",
+            Rank::Jack => "Jack",
+            Rank::Queen => "Queen",
+            Rank::King => "King",
+        }
+    }
+
+    /// One of: {Aces, Twos, Threes, ... Queens, Kings}
+    pub fn plural_name(&self) -> &'static str {
+        match self {
+            Rank::Ace => "Aces",
+            Rank::Two => "Twos",
+            Rank::Three => "Threes",
+            Rank::Four => "Fours",
+            Rank::Five => "Fives",
+            Rank::Six => "Sixes",
+            Rank::Seven => "Sevens",
+            Rank::Eight => "Eights",
+            Rank::Nine => "Nines",
+            Rank::Ten => "Tens",
+            Rank::Jack => "Jacks",
+            Rank::Queen => "Queens",
+            Rank::King => "Kings",
+        }
+    }
+}
+
+impl Ord for Rank {
+    fn cmp(&self, other: &Rank) -> Ordering {
+        self.cmp_with(other, AceOrder::High)
+    }
+}
+
+impl PartialOrd for Rank {
+    fn partial_cmp(&self, other: &Rank) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl TryFrom<u8> for Rank {
+    type Error = OutOfRange;
+
+    fn try_from(index: u8) -> Result<Rank, OutOfRange> {
+        Rank::ALL
+            .get(index as usize)
+            .copied()
+            .ok_or(OutOfRange(index))
+    }
 }
 
 /// One of:  {A, 2, 3, ... 8, 9, T, J, Q, K}
 impl fmt::Display for Rank {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let one_based_index = self.id + 1;
-
-        write!(
-            f,
-            "{}",
-            match one_based_index {
-                1 => String::from("A"),
-                10 => String::from("T"),
-                11 => String::from("J"),
-                12 => String::from("Q"),
-                13 => String::from("K"),
-                _ => one_based_index.to_string(),
-            }
-        )
+        match self {
+            Rank::Ace => write!(f, "A"),
+            Rank::Ten => write!(f, "T"),
+            Rank::Jack => write!(f, "J"),
+            Rank::Queen => write!(f, "Q"),
+            Rank::King => write!(f, "K"),
+            _ => write!(f, "{}", self.index() + 1),
+        }
     }
 }
 
 impl Card {
     pub const NUM_CARDS: usize = Suit::NUM_SUITS * Rank::NUM_RANKS;
 
-    #[allow(dead_code)]
+    /// Every card, in id order.
+    pub const ALL: [Card; Card::NUM_CARDS] = {
+        let mut cards = [Card { id: 0 }; Card::NUM_CARDS];
+        let mut id = 0;
+        while id < Card::NUM_CARDS {
+            cards[id] = Card { id: id as u8 };
+            id += 1;
+        }
+        cards
+    };
+
     pub fn new(rank: &Rank, suit: &Suit) -> Card {
         Card {
-            id: rank.id * Suit::NUM_SUITS + suit.id,
+            id: *rank as u8 * Suit::NUM_SUITS as u8 + *suit as u8,
         }
     }
 
-    /// Returns the suit (club, spade, heart, diamond) of this [`Card`],
-    /// where the suit is represented as an integer.
+    /// Every card in id order: the four aces, then the four twos, and so on.
+    pub fn iter() -> impl DoubleEndedIterator<Item = Card> + ExactSizeIterator {
+        Card::ALL.into_iter()
+    }
+
+    /// The compact id of the card, from 0 to 51.
+    pub fn id(&self) -> u8 {
+        self.id
+    }
+
+    /// Name with an ASCII letter for the suit, such as "Ah" or "Tc".
+    pub fn ascii_name(&self) -> String {
+        format!("{}{}", self.rank(), self.suit().ascii_name())
+    }
+
+    /// The id as an index into per-card arrays.
+    pub fn index(&self) -> usize {
+        self.id as usize
+    }
+
+    /// Returns the suit (club, spade, heart, diamond) of this [`Card`].
     pub fn suit(&self) -> Suit {
-        Suit {
-            id: (self.id % Suit::NUM_SUITS),
-        }
+        Suit::ALL[self.index() % Suit::NUM_SUITS]
     }
 
-    /// Returns the "index" (ace, two, three, ... jack, queen, king) of the [`Card`].
+    /// Returns the rank (ace, two, three, ... jack, queen, king) of the [`Card`].
     pub fn rank(&self) -> Rank {
-        Rank {
-            id: (self.id / Suit::NUM_SUITS),
-        }
+        Rank::ALL[self.index() / Suit::NUM_SUITS]
     }
 
     pub fn draw_random_card<R: Rng>(rng: &mut R) -> Card {
-        Card {
-            id: rng.gen_range(0..Card::NUM_CARDS),
-        }
+        Card::ALL[rng.gen_range(0..Card::NUM_CARDS)]
+    }
+}
+
+impl TryFrom<u8> for Card {
+    type Error = OutOfRange;
+
+    fn try_from(id: u8) -> Result<Card, OutOfRange> {
+        Card::ALL.get(id as usize).copied().ok_or(OutOfRange(id))
+    }
+}
+
+impl Ord for Card {
+    fn cmp(&self, other: &Card) -> Ordering {
+        (self.rank(), self.suit()).cmp(&(other.rank(), other.suit()))
+    }
+}
+
+impl PartialOrd for Card {
+    fn partial_cmp(&self, other: &Card) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl From<Card> for u8 {
+    fn from(card: Card) -> u8 {
+        card.id
     }
 }
 
@@ -104,25 +340,116 @@ impl fmt::Display for Card {
     }
 }
 
+/// Parses the display name ("A♥") or the ASCII name ("Ah") of a card.
+impl FromStr for Card {
+    type Err = ParseCardError;
+
+    fn from_str(name: &str) -> Result<Card, ParseCardError> {
+        let mut chars = name.chars();
+        let (Some(rank), Some(suit), None) = (chars.next(), chars.next(), chars.next()) else {
+            return Err(ParseCardError(name.to_string()));
+        };
+        let rank = Rank::iter().find(|r| r.to_string().starts_with(rank));
+        let suit = Suit::iter().find(|s| s.ascii_name() == suit || s.to_string().starts_with(suit));
+        match (rank, suit) {
+            (Some(rank), Some(suit)) => Ok(Card::new(&rank, &suit)),
+            _ => Err(ParseCardError(name.to_string())),
+        }
+    }
+}
+
+/// Cards are written as their ASCII name, such as "Ah".
+#[cfg(feature = "serde")]
+impl serde::Serialize for Card {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.ascii_name())
+    }
+}
+
+/// Reads either the ASCII name or the display name of a card.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Card {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Card, D::Error> {
+        let name = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        name.parse().map_err(serde::de::Error::custom)
+    }
+}
+
 //////////////////////////////////////////////////////////////////////////////////////
 
 #[cfg(test)]
 mod tests {
 
+    use crate::card::AceOrder;
     use crate::card::Card;
+    use crate::card::OutOfRange;
+    use crate::card::ParseCardError;
     use crate::card::Rank;
     use crate::card::Suit;
+    use crate::error::PokerError;
 
     #[test]
     fn card_constructor_from_rank_and_suit_test() {
-        for rank_id in 0..Rank::NUM_RANKS {
-            for suit_id in 0..Suit::NUM_SUITS {
-                let rank = Rank { id: rank_id };
-                let suit = Suit { id: suit_id };
+        for rank in Rank::iter() {
+            for suit in Suit::iter() {
                 let card = Card::new(&rank, &suit);
                 assert_eq!(card.suit(), suit);
                 assert_eq!(card.rank(), rank);
             }
         }
     }
+
+    #[test]
+    fn conversions_from_bytes() {
+        assert_eq!(std::mem::size_of::<Card>(), 1);
+        assert_eq!(Card::iter().count(), Card::NUM_CARDS);
+        for card in Card::iter() {
+            assert_eq!(Card::try_from(card.id()), Ok(card));
+            assert_eq!(u8::from(card), card.id());
+        }
+        assert_eq!(Card::try_from(52), Err(OutOfRange(52)));
+        assert_eq!(Rank::try_from(12), Ok(Rank::King));
+        assert_eq!(Rank::try_from(13), Err(OutOfRange(13)));
+        assert_eq!(Suit::try_from(2), Ok(Suit::Hearts));
+        assert_eq!(Suit::try_from(4), Err(OutOfRange(4)));
+        assert!(matches!(
+            PokerError::from(OutOfRange(4)),
+            PokerError::OutOfRange(4)
+        ));
+        assert_eq!(Card::new(&Rank::Ten, &Suit::Spades).to_string(), "T♠");
+    }
+
+    #[test]
+    fn ordering() {
+        assert!(Rank::Ace > Rank::King && Rank::Two < Rank::Three);
+        assert!(Rank::Ace.cmp_with(&Rank::Two, AceOrder::Low).is_lt());
+        assert_eq!(Rank::Ace.strength(AceOrder::High), 12);
+        let mut cards: Vec<Card> = Card::iter().collect();
+        cards.sort();
+        assert_eq!(cards[0], Card::new(&Rank::Two, &Suit::Clubs));
+        assert_eq!(cards[3], Card::new(&Rank::Two, &Suit::Spades));
+        assert_eq!(cards[51], Card::new(&Rank::Ace, &Suit::Spades));
+    }
+
+    #[test]
+    fn parse_names() {
+        for card in Card::iter() {
+            assert_eq!(card.ascii_name().parse(), Ok(card));
+            assert_eq!(card.to_string().parse(), Ok(card));
+        }
+        for name in ["1h", "Ahh", ""] {
+            assert_eq!(name.parse::<Card>(), Err(ParseCardError(name.to_string())));
+        }
+        let error = PokerError::from("1h".parse::<Card>().unwrap_err());
+        assert!(matches!(error, PokerError::InvalidCardName(name) if name == "1h"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_names() {
+        let card = Card::new(&Rank::Ace, &Suit::Hearts);
+        assert_eq!(serde_json::to_string(&card).unwrap(), "\"Ah\"");
+        assert_eq!(serde_json::from_str::<Card>("\"A♥\"").unwrap(), card);
+        assert!(serde_json::from_str::<Card>("\"Ax\"").is_err());
+    }
 }
@@ -0,0 +1,146 @@
+use crate::card::Card;
+
+/// Number of ways to choose `k` items from `n`.
+pub fn binomial(n: usize, k: usize) -> usize {
+    if k > n {
+        return 0;
+    }
+    (0..k).fold(1, |acc, i| acc * (n - i) / (i + 1))
+}
+
+/// A running total for every set of zero up to `max_size` cards. Adding a
+/// value for a hand adds it to the total of every subset of that hand, so
+/// that afterwards the total for a set of cards is the sum over all hands
+/// that contain those cards. Inclusion-exclusion then gives the sum over the
+/// hands that contain some cards and avoid others, which is the question
+/// that comes up whenever cards are dealt from a partially known deck.
+///
+/// Each set of `k` cards with sorted ids `c_1 < ... < c_k` is stored at its
+/// index in the combinatorial number system, `C(c_1, 1) + ... + C(c_k, k)`.
+/// All of the methods take the cards of a hand sorted by id, and select a
+/// subset of them with a bit mask.
+#[derive(Clone)]
+pub struct CardSubsets {
+    totals: Vec<Vec<i64>>,
+    /// `C(n, k)` is stored at `n * (max_size + 1) + k`.
+    binomials: Vec<usize>,
+    max_size: usize,
+}
+
+impl CardSubsets {
+    pub fn new(max_size: usize) -> CardSubsets {
+        let binomials: Vec<usize> = (0..=Card::NUM_CARDS)
+            .flat_map(|n| (0..=max_size).map(move |k| binomial(n, k)))
+            .collect();
+        CardSubsets {
+            totals: (0..=max_size)
+                .map(|k| vec![0; binomial(Card::NUM_CARDS, k)])
+                .collect(),
+            binomials,
+            max_size,
+        }
+    }
+
+    /// Size and index of the subset of `cards` selected by `mask`, or `None`
+    /// if the subset has more than `max_size` cards.
+    fn locate(&self, cards: &[Card], mask: usize) -> Option<(usize, usize)> {
+        let mut k = 0;
+        let mut index = 0;
+        for (i, card) in cards.iter().enumerate() {
+            if mask & (1 << i) != 0 {
+                k += 1;
+                if k > self.max_size {
+                    return None;
+                }
+                index += self.binomials[card.index() * (self.max_size + 1) + k];
+            }
+        }
+        Some((k, index))
+    }
+
+    /// Total for the subset of `cards` selected by `mask`. Sets larger than
+    /// `max_size` are not in any hand, so their total is zero.
+    pub fn get(&self, cards: &[Card], mask: usize) -> i64 {
+        self.locate(cards, mask)
+            .map_or(0, |(k, index)| self.totals[k][index])
+    }
+
+    /// Adds `value` to the total of every subset of `cards`.
+    pub fn add_to_subsets(&mut self, cards: &[Card], value: i64) {
+        for mask in 0..1 << cards.len() {
+            if let Some((k, index)) = self.locate(cards, mask) {
+                self.totals[k][index] += value;
+            }
+        }
+    }
+
+    /// Totals for every subset of `cards`, indexed by mask. Useful to avoid
+    /// repeated lookups when calling `inclusion_exclusion` for many masks.
+    pub fn subset_totals(&self, cards: &[Card]) -> Vec<i64> {
+        (0..1 << cards.len())
+            .map(|mask| self.get(cards, mask))
+            .collect()
+    }
+
+    /// Sum over the hands that contain every card of `cards` selected by
+    /// `mask`, and none of the others. With a `mask` of zero, this is the sum
+    /// over all hands that could still be dealt after `cards` are removed.
+    pub fn total_avoiding_others(&self, cards: &[Card], mask: usize) -> i64 {
+        CardSubsets::inclusion_exclusion(&self.subset_totals(cards), mask)
+    }
+
+    /// Same as `total_avoiding_others`, from the `subset_totals` of the cards.
+    pub fn inclusion_exclusion(subset_totals: &[i64], mask: usize) -> i64 {
+        let others = subset_totals.len() - 1 - mask;
+        let mut total = 0;
+        // Visit every subset of the other cards.
+        let mut extra = others;
+        loop {
+            let value = subset_totals[mask | extra];
+            if extra.count_ones().is_multiple_of(2) {
+                total += value;
+            } else {
+                total -= value;
+            }
+            if extra == 0 {
+                break;
+            }
+            extra = (extra - 1) & others;
+        }
+        total
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use crate::card::Card;
+    use crate::card_subsets::binomial;
+    use crate::card_subsets::CardSubsets;
+    use crate::hand::for_each_hand_in_shoe;
+
+    #[test]
+    fn binomial_test() {
+        assert_eq!(binomial(52, 5), 2598960);
+        assert_eq!(binomial(47, 0), 1);
+        assert_eq!(binomial(3, 4), 0);
+    }
+
+    /// Count two card hands, and check the counts against a direct search.
+    #[test]
+    fn count_hands_avoiding_cards() {
+        let mut subsets = CardSubsets::new(2);
+        for_each_hand_in_shoe::<2, _>(1, |hand, _| {
+            subsets.add_to_subsets(&hand.cards, 1);
+        });
+        let cards = [Card::ALL[3], Card::ALL[10], Card::ALL[40]];
+        assert_eq!(subsets.get(&cards, 0), 1326);
+        assert_eq!(subsets.get(&cards, 0b001), 51);
+        assert_eq!(subsets.get(&cards, 0b011), 1);
+        // Hands that avoid all three cards: C(49, 2).
+        assert_eq!(subsets.total_avoiding_others(&cards, 0), 1176);
+        // Hands with the first card, but neither of the others.
+        assert_eq!(subsets.total_avoiding_others(&cards, 0b001), 49);
+    }
+}
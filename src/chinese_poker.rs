@@ -0,0 +1,597 @@
+use crate::card::{Card, Rank};
+use crate::deck::remaining_cards;
+use crate::error::Result;
+use crate::hand::{check_unique, Hand};
+use crate::hand_value::{ace_high_index, HandCategory, HandValue};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::cmp::Ordering;
+use std::fmt;
+
+pub const CHINESE_POKER_HAND_SIZE: usize = 13;
+pub const TOP_ROW_SIZE: usize = 3;
+pub const ROW_SIZE: usize = 5;
+
+/// Rows are scored 1 point each, and winning all three (a scoop) earns 3 more.
+const SCOOP_POINTS: i32 = 6;
+
+#[derive(PartialEq, Eq, Debug, Copy, Clone, Hash)]
+pub enum Row {
+    Top,
+    Middle,
+    Bottom,
+}
+
+impl Row {
+    pub const ALL: [Row; 3] = [Row::Top, Row::Middle, Row::Bottom];
+
+    pub fn size(&self) -> usize {
+        match self {
+            Row::Top => TOP_ROW_SIZE,
+            Row::Middle | Row::Bottom => ROW_SIZE,
+        }
+    }
+
+    /// Bonus points for the hand in this row, using the common open-face
+    /// royalties. The three card top row only pays for a pair of sixes or
+    /// better, and for trips.
+    pub fn royalty(&self, value: &HandValue) -> u32 {
+        let top_rank = || ace_high_index(value.tiebreak_ranks()[0]) as u32;
+        match (self, value.category()) {
+            (Row::Top, HandCategory::Pair) => {
+                top_rank().saturating_sub(ace_high_index(Rank::Five) as u32)
+            }
+            (Row::Top, HandCategory::ThreeOfAKind) => 10 + top_rank(),
+            (Row::Top, _) => 0,
+            (Row::Middle, category) => 2 * Row::Middle.royalty_for_category(category, top_rank()),
+            (Row::Bottom, category) => Row::Bottom.royalty_for_category(category, top_rank()),
+        }
+    }
+
+    /// Bottom row royalties, which the middle row pays double of. The middle
+    /// row also pays for trips.
+    fn royalty_for_category(&self, category: HandCategory, top_rank: u32) -> u32 {
+        match category {
+            HandCategory::ThreeOfAKind if *self == Row::Middle => 1,
+            HandCategory::Straight => 2,
+            HandCategory::Flush => 4,
+            HandCategory::FullHouse => 6,
+            HandCategory::FourOfAKind => 10,
+            HandCategory::StraightFlush if top_rank == ace_high_index(Rank::Ace) as u32 => 25,
+            HandCategory::StraightFlush => 15,
+            _ => 0,
+        }
+    }
+}
+
+/// Thirteen cards set into three rows. The arrangement is only valid if the
+/// bottom row is at least as strong as the middle row, which in turn is at
+/// least as strong as the top row; otherwise the hand is fouled.
+#[derive(PartialEq, Debug)]
+pub struct Arrangement {
+    pub top: Hand<TOP_ROW_SIZE>,
+    pub middle: Hand<ROW_SIZE>,
+    pub bottom: Hand<ROW_SIZE>,
+}
+
+/// What matters about an arrangement for scoring: the value of each row from
+/// top to bottom (or `None` if fouled), and the royalties it earns.
+#[derive(PartialEq, Debug, Copy, Clone)]
+struct ArrangementScore {
+    values: Option<[HandValue; 3]>,
+    royalties: u32,
+}
+
+impl ArrangementScore {
+    fn new(values: [HandValue; 3]) -> ArrangementScore {
+        if values[0] > values[1] || values[1] > values[2] {
+            return ArrangementScore {
+                values: None,
+                royalties: 0,
+            };
+        }
+        ArrangementScore {
+            values: Some(values),
+            royalties: Row::ALL
+                .iter()
+                .zip(values.iter())
+                .map(|(row, value)| row.royalty(value))
+                .sum(),
+        }
+    }
+
+    /// Points won from the other player, which are negative for a loss. A
+    /// fouled hand loses every row and pays the other player's royalties.
+    fn points_against(&self, other: &ArrangementScore) -> i32 {
+        match (self.values, other.values) {
+            (None, None) => 0,
+            (None, Some(_)) => -SCOOP_POINTS - other.royalties as i32,
+            (Some(_), None) => SCOOP_POINTS + self.royalties as i32,
+            (Some(values), Some(other_values)) => {
+                let rows: i32 = values
+                    .iter()
+                    .zip(other_values.iter())
+                    .map(|(value, other_value)| match value.cmp(other_value) {
+                        Ordering::Greater => 1,
+                        Ordering::Less => -1,
+                        Ordering::Equal => 0,
+                    })
+                    .sum();
+                let rows = if rows.abs() == 3 { 2 * rows } else { rows };
+                rows + self.royalties as i32 - other.royalties as i32
+            }
+        }
+    }
+}
+
+impl Arrangement {
+    fn score(&self) -> ArrangementScore {
+        ArrangementScore::new([
+            HandValue::from(&self.top),
+            HandValue::from(&self.middle),
+            HandValue::from(&self.bottom),
+        ])
+    }
+
+    pub fn is_foul(&self) -> bool {
+        self.score().values.is_none()
+    }
+
+    /// Total royalties of the three rows, or zero if the hand is fouled.
+    pub fn royalties(&self) -> u32 {
+        self.score().royalties
+    }
+
+    /// A valid hand with queens or better in the top row earns fantasyland in
+    /// open-face Chinese poker.
+    pub fn is_fantasyland(&self) -> bool {
+        let top = HandValue::from(&self.top);
+        !self.is_foul()
+            && match top.category() {
+                HandCategory::Pair => {
+                    ace_high_index(top.tiebreak_ranks()[0]) >= ace_high_index(Rank::Queen)
+                }
+                category => category > HandCategory::Pair,
+            }
+    }
+
+    /// Points won against another arrangement, using the 1-6 scoring: one
+    /// point per row, three more for winning all three, plus the difference
+    /// in royalties.
+    pub fn points_against(&self, other: &Arrangement) -> i32 {
+        self.score().points_against(&other.score())
+    }
+}
+
+impl fmt::Display for Arrangement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let row = |cards: &[Card]| {
+            cards
+                .iter()
+                .map(|card| card.to_string())
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        write!(
+            f,
+            "Top:    {}\nMiddle: {}\nBottom: {}",
+            row(&self.top.cards),
+            row(&self.middle.cards),
+            row(&self.bottom.cards)
+        )
+    }
+}
+
+/// Every valid way to set 13 cards, as bit masks of the top and middle rows
+/// (the bottom row gets the rest) along with the score of each.
+fn valid_arrangements(
+    cards: &[Card; CHINESE_POKER_HAND_SIZE],
+) -> Vec<(u16, u16, ArrangementScore)> {
+    let all: u16 = (1 << CHINESE_POKER_HAND_SIZE) - 1;
+    let value = |mask: u16| {
+        let selected: Vec<Card> = (0..CHINESE_POKER_HAND_SIZE)
+            .filter(|i| mask & (1 << i) != 0)
+            .map(|i| cards[i])
+            .collect();
+        HandValue::from_cards(&selected)
+    };
+    // There are only C(13, 5) five card rows, so evaluate each of them once.
+    let values: Vec<Option<HandValue>> = (0..=all)
+        .map(|mask| match mask.count_ones() as usize {
+            TOP_ROW_SIZE | ROW_SIZE => Some(value(mask)),
+            _ => None,
+        })
+        .collect();
+
+    let mut arrangements = Vec::new();
+    for top in (0..=all).filter(|mask| mask.count_ones() as usize == TOP_ROW_SIZE) {
+        let rest = all ^ top;
+        // Visit every subset of the remaining cards.
+        let mut middle = rest;
+        while middle != 0 {
+            if middle.count_ones() as usize == ROW_SIZE {
+                let score = ArrangementScore::new([
+                    values[top as usize].unwrap(),
+                    values[middle as usize].unwrap(),
+                    values[(rest ^ middle) as usize].unwrap(),
+                ]);
+                if score.values.is_some() {
+                    arrangements.push((top, middle, score));
+                }
+            }
+            middle = (middle - 1) & rest;
+        }
+    }
+    arrangements
+}
+
+fn arrangement_from_masks(
+    cards: &[Card; CHINESE_POKER_HAND_SIZE],
+    top: u16,
+    middle: u16,
+) -> Arrangement {
+    let row = |mask: u16| {
+        (0..CHINESE_POKER_HAND_SIZE)
+            .filter(move |i| mask & (1 << i) != 0)
+            .map(|i| cards[i])
+    };
+    let mut top_cards = row(top);
+    let mut middle_cards = row(middle);
+    let mut bottom_cards = row(!(top | middle));
+    Arrangement {
+        top: Hand {
+            cards: array_init::array_init(|_| top_cards.next().unwrap()),
+        },
+        middle: Hand {
+            cards: array_init::array_init(|_| middle_cards.next().unwrap()),
+        },
+        bottom: Hand {
+            cards: array_init::array_init(|_| bottom_cards.next().unwrap()),
+        },
+    }
+}
+
+/// The arrangement of the opponents: the most royalties, breaking ties in
+/// favour of the strongest middle row and then the strongest top row.
+fn most_royalties(cards: &[Card; CHINESE_POKER_HAND_SIZE]) -> ArrangementScore {
+    valid_arrangements(cards)
+        .into_iter()
+        .map(|(_, _, score)| score)
+        .max_by_key(|score| {
+            let values = score.values.unwrap();
+            (score.royalties, values[1], values[0])
+        })
+        .unwrap()
+}
+
+#[derive(PartialEq, Debug)]
+pub struct ArrangementAnalysis {
+    pub arrangement: Arrangement,
+    pub royalties: u32,
+    /// Average points won against the sampled opponents.
+    pub expected_points: f64,
+}
+
+/// Finds the best way to set 13 cards for Chinese poker. Every valid
+/// arrangement is scored against the same `num_opponents` random opponent
+/// hands, dealt from the remaining cards and set to earn the most royalties,
+/// and the arrangement with the most points on average is returned. Fails
+/// if the cards are not unique.
+pub fn best_arrangement<R: Rng>(
+    rng: &mut R,
+    cards: &[Card; CHINESE_POKER_HAND_SIZE],
+    num_opponents: u32,
+) -> Result<ArrangementAnalysis> {
+    check_unique(cards)?;
+
+    let mut stub = remaining_cards(cards);
+    let opponents: Vec<ArrangementScore> = (0..num_opponents)
+        .map(|_| {
+            let (dealt, _) = stub.partial_shuffle(rng, CHINESE_POKER_HAND_SIZE);
+            most_royalties(&array_init::array_init(|i| dealt[i]))
+        })
+        .collect();
+
+    // Setting the cards from the strongest down never fouls, so there is
+    // always at least one valid arrangement.
+    let (top, middle, score, expected_points) = valid_arrangements(cards)
+        .into_iter()
+        .map(|(top, middle, score)| {
+            let total: i32 = opponents
+                .iter()
+                .map(|opponent| score.points_against(opponent))
+                .sum();
+            (
+                top,
+                middle,
+                score,
+                total as f64 / num_opponents.max(1) as f64,
+            )
+        })
+        .max_by(|a, b| {
+            (a.3, a.2.royalties)
+                .partial_cmp(&(b.3, b.2.royalties))
+                .unwrap()
+        })
+        .unwrap();
+    Ok(ArrangementAnalysis {
+        arrangement: arrangement_from_masks(cards, top, middle),
+        royalties: score.royalties,
+        expected_points,
+    })
+}
+
+/// The cards placed so far in an open-face Chinese poker hand.
+#[derive(Default, PartialEq, Debug, Clone)]
+pub struct OfcRows {
+    pub top: Vec<Card>,
+    pub middle: Vec<Card>,
+    pub bottom: Vec<Card>,
+}
+
+impl OfcRows {
+    pub fn row(&self, row: Row) -> &Vec<Card> {
+        match row {
+            Row::Top => &self.top,
+            Row::Middle => &self.middle,
+            Row::Bottom => &self.bottom,
+        }
+    }
+
+    fn row_mut(&mut self, row: Row) -> &mut Vec<Card> {
+        match row {
+            Row::Top => &mut self.top,
+            Row::Middle => &mut self.middle,
+            Row::Bottom => &mut self.bottom,
+        }
+    }
+
+    pub fn has_room(&self, row: Row) -> bool {
+        self.row(row).len() < row.size()
+    }
+
+    fn arrangement(&self) -> Arrangement {
+        Arrangement {
+            top: Hand {
+                cards: array_init::array_init(|i| self.top[i]),
+            },
+            middle: Hand {
+                cards: array_init::array_init(|i| self.middle[i]),
+            },
+            bottom: Hand {
+                cards: array_init::array_init(|i| self.bottom[i]),
+            },
+        }
+    }
+}
+
+/// Decides which row each card goes into in open-face Chinese poker, where
+/// cards are placed one at a time and can never be moved. Any closure
+/// `Fn(&OfcRows, Card) -> Row` is also a strategy.
+pub trait OfcPlacement: Sync {
+    fn place(&self, rows: &OfcRows, card: Card) -> Row;
+}
+
+impl<F> OfcPlacement for F
+where
+    F: Fn(&OfcRows, Card) -> Row + Sync,
+{
+    fn place(&self, rows: &OfcRows, card: Card) -> Row {
+        self(rows, card)
+    }
+}
+
+/// A simple strategy that keeps the rows in order: pair a card up with a row
+/// that already holds its rank, from the bottom up. Otherwise high cards go
+/// to the bottom row, middling cards to the middle, and low cards to the top.
+#[derive(Default, Debug, Clone, Copy)]
+pub struct PairPlacement;
+
+impl OfcPlacement for PairPlacement {
+    fn place(&self, rows: &OfcRows, card: Card) -> Row {
+        let open = |row: &&Row| rows.has_room(**row);
+        if let Some(row) = [Row::Bottom, Row::Middle].iter().filter(open).find(|row| {
+            rows.row(**row)
+                .iter()
+                .any(|other| other.rank() == card.rank())
+        }) {
+            return *row;
+        }
+        let preferred = match ace_high_index(card.rank()) {
+            rank if rank >= ace_high_index(Rank::Ten) => [Row::Bottom, Row::Middle, Row::Top],
+            rank if rank >= ace_high_index(Rank::Seven) => [Row::Middle, Row::Bottom, Row::Top],
+            _ => [Row::Top, Row::Middle, Row::Bottom],
+        };
+        *preferred.iter().find(open).unwrap()
+    }
+}
+
+#[derive(Default, PartialEq, Debug)]
+pub struct OfcStats {
+    pub num_samples: u32,
+    pub fouls: u32,
+    pub fantasylands: u32,
+    pub royalties: u64,
+}
+
+impl OfcStats {
+    pub fn insert(&mut self, other: &OfcStats) {
+        self.num_samples += other.num_samples;
+        self.fouls += other.fouls;
+        self.fantasylands += other.fantasylands;
+        self.royalties += other.royalties;
+    }
+
+    pub fn foul_rate(&self) -> f64 {
+        self.fouls as f64 / self.num_samples as f64
+    }
+
+    pub fn fantasyland_rate(&self) -> f64 {
+        self.fantasylands as f64 / self.num_samples as f64
+    }
+
+    pub fn average_royalties(&self) -> f64 {
+        self.royalties as f64 / self.num_samples as f64
+    }
+}
+
+impl fmt::Display for OfcStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "OfcStats: {} samples\nFoul:        {:>7.3}%\nFantasyland: {:>7.3}%\nRoyalties:   {:>7.3}",
+            self.num_samples,
+            100.0 * self.foul_rate(),
+            100.0 * self.fantasyland_rate(),
+            self.average_royalties()
+        )
+    }
+}
+
+/// Plays `num_samples` open-face hands, dealing the 13 cards one at a time
+/// and placing each with `placement`. A placement into a full row is moved to
+/// the first row with room, from the bottom up.
+pub fn simulate_ofc<R: Rng>(
+    rng: &mut R,
+    placement: &dyn OfcPlacement,
+    num_samples: u32,
+) -> OfcStats {
+    let mut stats = OfcStats {
+        num_samples,
+        ..Default::default()
+    };
+    let mut deck = remaining_cards(&[]);
+    for _ in 0..num_samples {
+        let (dealt, _) = deck.partial_shuffle(rng, CHINESE_POKER_HAND_SIZE);
+        let mut rows = OfcRows::default();
+        for card in dealt.iter() {
+            let mut row = placement.place(&rows, *card);
+            if !rows.has_room(row) {
+                row = *[Row::Bottom, Row::Middle, Row::Top]
+                    .iter()
+                    .find(|row| rows.has_room(**row))
+                    .unwrap();
+            }
+            rows.row_mut(row).push(*card);
+        }
+        let arrangement = rows.arrangement();
+        if arrangement.is_foul() {
+            stats.fouls += 1;
+        }
+        if arrangement.is_fantasyland() {
+            stats.fantasylands += 1;
+        }
+        stats.royalties += arrangement.royalties() as u64;
+    }
+    stats
+}
+
+//////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use crate::card::Card;
+    use crate::chinese_poker::best_arrangement;
+    use crate::chinese_poker::simulate_ofc;
+    use crate::chinese_poker::Arrangement;
+    use crate::chinese_poker::OfcRows;
+    use crate::chinese_poker::PairPlacement;
+    use crate::chinese_poker::Row;
+    use crate::deck::Deck;
+    use crate::hand_value::HandValue;
+    use rand::SeedableRng;
+
+    fn arrangement(
+        deck: &Deck,
+        top: &[&str; 3],
+        middle: &[&str; 5],
+        bottom: &[&str; 5],
+    ) -> Arrangement {
+        Arrangement {
+            top: deck.draw_hand(top).unwrap(),
+            middle: deck.draw_hand(middle).unwrap(),
+            bottom: deck.draw_hand(bottom).unwrap(),
+        }
+    }
+
+    #[test]
+    fn royalties() {
+        let deck = Deck::new();
+        let value = |names: &[&str]| HandValue::from_cards(&deck.draw_cards(names).unwrap());
+        assert_eq!(Row::Top.royalty(&value(&["5♠", "5♦", "A♣"])), 0);
+        assert_eq!(Row::Top.royalty(&value(&["6♠", "6♦", "A♣"])), 1);
+        assert_eq!(Row::Top.royalty(&value(&["A♠", "A♦", "K♣"])), 9);
+        assert_eq!(Row::Top.royalty(&value(&["2♠", "2♦", "2♣"])), 10);
+        assert_eq!(Row::Top.royalty(&value(&["A♠", "A♦", "A♣"])), 22);
+        let flush = value(&["5♣", "9♣", "8♣", "7♣", "2♣"]);
+        assert_eq!(Row::Middle.royalty(&flush), 8);
+        assert_eq!(Row::Bottom.royalty(&flush), 4);
+        let trips = value(&["5♣", "5♦", "5♥", "7♣", "2♣"]);
+        assert_eq!(Row::Middle.royalty(&trips), 2);
+        assert_eq!(Row::Bottom.royalty(&trips), 0);
+        let royal = value(&["T♥", "J♥", "Q♥", "K♥", "A♥"]);
+        assert_eq!(Row::Bottom.royalty(&royal), 25);
+    }
+
+    #[test]
+    fn fouls_and_points() {
+        let deck = Deck::new();
+        let good = arrangement(
+            &deck,
+            &["Q♠", "Q♦", "3♣"],
+            &["5♣", "5♦", "5♥", "7♣", "2♣"],
+            &["9♣", "9♦", "9♥", "8♣", "8♦"],
+        );
+        assert!(!good.is_foul());
+        assert!(good.is_fantasyland());
+        assert_eq!(good.royalties(), 7 + 2 + 6);
+        let fouled = arrangement(
+            &deck,
+            &["A♠", "A♦", "3♦"],
+            &["K♣", "T♦", "J♥", "7♦", "2♦"],
+            &["4♣", "4♦", "6♥", "8♥", "T♣"],
+        );
+        assert!(fouled.is_foul());
+        assert!(!fouled.is_fantasyland());
+        assert_eq!(fouled.royalties(), 0);
+        assert_eq!(good.points_against(&fouled), 6 + 15);
+        assert_eq!(fouled.points_against(&good), -6 - 15);
+        assert_eq!(good.points_against(&good), 0);
+    }
+
+    #[test]
+    fn best_arrangement_is_valid() {
+        let deck = Deck::new();
+        let mut rng = rand::rngs::StdRng::seed_from_u64(1313);
+        let cards: [Card; 13] = deck
+            .draw_cards(&[
+                "A♠", "A♦", "K♣", "K♦", "7♠", "7♦", "7♣", "2♥", "3♥", "4♥", "9♥", "J♥", "5♠",
+            ])
+            .unwrap()
+            .try_into()
+            .unwrap();
+        let analysis = best_arrangement(&mut rng, &cards, 50).unwrap();
+        assert!(!analysis.arrangement.is_foul());
+        assert_eq!(analysis.royalties, analysis.arrangement.royalties());
+        assert!(analysis.expected_points > 0.0);
+        let mut duplicates = cards;
+        duplicates[1] = duplicates[0];
+        assert!(best_arrangement(&mut rng, &duplicates, 10).is_err());
+    }
+
+    #[test]
+    fn ofc_statistics() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(77);
+        let stats = simulate_ofc(&mut rng, &PairPlacement, 2000);
+        assert_eq!(stats.num_samples, 2000);
+        assert!(stats.fouls > 0 && stats.fouls < 1000);
+        // Filling the top row first fouls far more often.
+        let top_first =
+            |rows: &OfcRows, _: Card| *Row::ALL.iter().find(|row| rows.has_room(**row)).unwrap();
+        let top_first_stats = simulate_ofc(&mut rng, &top_first, 2000);
+        assert!(top_first_stats.fouls > stats.fouls);
+        assert!(stats
+            .to_string()
+            .starts_with("OfcStats: 2000 samples\nFoul:         42.150%\n"));
+    }
+}
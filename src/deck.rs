@@ -1,56 +1,65 @@
 use crate::card::Card;
 use crate::card::Rank;
 use crate::card::Suit;
+use crate::error::{PokerError, Result};
 use crate::hand::Hand;
 use std::collections::HashMap;
 
 /// Represent a standard deck of cards with all suits and ranks present.
 /// The primary use of this data structure is to perform quick lookup for
 /// cards by name, which is implemented by a HashMap behind the scenes.
+/// Cards can be looked up by their display name ("A♦") or with an ASCII
+/// letter for the suit ("Ad"), which is easier to type on the command line.
 pub struct Deck {
     cards_by_name: HashMap<String, Card>,
 }
 
 impl Deck {
-    #[allow(dead_code)]
     pub fn new() -> Deck {
         let mut deck = Deck {
             cards_by_name: HashMap::new(),
         };
-        for rank_id in 0..Rank::NUM_RANKS {
-            for suit_id in 0..Suit::NUM_SUITS {
-                let rank = Rank { id: rank_id };
-                let suit = Suit { id: suit_id };
+        for rank in Rank::iter() {
+            for suit in Suit::iter() {
                 let card = Card::new(&rank, &suit);
                 deck.cards_by_name.insert(card.to_string(), card);
+                deck.cards_by_name.insert(card.ascii_name(), card);
             }
         }
         deck
     }
 
-    #[allow(dead_code)]
-    pub fn draw_card(&self, card_name: &str) -> Option<&Card> {
-        self.cards_by_name.get(card_name)
+    pub fn draw_card(&self, card_name: &str) -> Result<Card> {
+        self.cards_by_name
+            .get(card_name)
+            .copied()
+            .ok_or_else(|| PokerError::InvalidCardName(card_name.to_string()))
     }
 
-    #[allow(dead_code)]
-    pub fn draw_hand<const N: usize>(&self, card_names: &[&str; N]) -> Option<Hand<N>> {
-        let mut ok = true;
-        let hand = Hand {
-            cards: array_init::array_init(|i| {
-                if let Some(card) = self.draw_card(card_names[i]) {
-                    *card
-                } else {
-                    ok = false;
-                    Card { id: 0 }
-                }
-            }),
-        };
-        if ok {
-            Some(hand)
-        } else {
-            None
-        }
+    /// Looks up every card by name. The same card may be named more than
+    /// once, as in a hand dealt from a shoe of several decks.
+    pub fn draw_hand<const N: usize>(&self, card_names: &[&str; N]) -> Result<Hand<N>> {
+        let cards = self.draw_cards(card_names)?;
+        Ok(Hand {
+            cards: array_init::array_init(|i| cards[i]),
+        })
+    }
+
+    /// Same as `draw_hand`, but for any number of cards.
+    pub fn draw_cards(&self, card_names: &[&str]) -> Result<Vec<Card>> {
+        card_names.iter().map(|name| self.draw_card(name)).collect()
+    }
+}
+
+/// Returns every card in a standard deck that is not in `dealt`, in sorted
+/// order. This is the stub that the remaining cards of a hand are drawn from.
+pub fn remaining_cards(dealt: &[Card]) -> Vec<Card> {
+    Card::iter().filter(|card| !dealt.contains(card)).collect()
+}
+
+impl Default for Deck {
+    fn default() -> Self {
+        Deck::new()
     }
 }
 
@@ -59,16 +68,28 @@ impl Deck {
 #[cfg(test)]
 mod tests {
 
+    use crate::deck::remaining_cards;
     use crate::deck::Deck;
+    use crate::error::PokerError;
 
     #[test]
     fn draw_card_by_name_test() {
         let deck = Deck::new();
-        for card_name in vec!["A♦", "5♥", "Q♠", "2♣"]
-            .into_iter()
-            .map(|name_slice| String::from(name_slice))
-        {
+        for card_name in vec!["A♦", "5♥", "Q♠", "2♣"].into_iter().map(String::from) {
             assert_eq!(deck.draw_card(&card_name).unwrap().to_string(), card_name);
         }
     }
+
+    #[test]
+    fn remaining_cards_test() {
+        let deck = Deck::new();
+        let dealt = deck.draw_cards(&["A♦", "5♥", "Q♠"]).unwrap();
+        let remaining = remaining_cards(&dealt);
+        assert_eq!(remaining.len(), 49);
+        assert!(dealt.iter().all(|card| !remaining.contains(card)));
+        assert!(matches!(
+            deck.draw_cards(&["A♦", "5X"]),
+            Err(PokerError::InvalidCardName(name)) if name == "5X"
+        ));
+    }
 }
@@ -0,0 +1,220 @@
+use crate::aggregate_score::AggregateScore;
+use crate::card::Suit;
+use crate::deck::remaining_cards;
+use crate::error::Result;
+use crate::hand::Hand;
+use crate::hand_score::HandScore;
+use crate::hand_stats::HandStats;
+use crate::hand_value::{ace_high_index, HandCategory, HandValue};
+use crate::parallel::{batch_rng, parallel_batches};
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+pub const DRAW_HAND_SIZE: usize = 5;
+
+/// Which cards of the dealt hand to keep: `true` keeps the card at that
+/// index, and `false` discards it to be replaced on the draw.
+pub type Holds = [bool; DRAW_HAND_SIZE];
+
+/// Decides which cards to keep from a five card hand before the draw.
+/// Any closure `Fn(&Hand<5>) -> Holds` is also a strategy.
+pub trait DiscardStrategy: Sync {
+    fn hold(&self, hand: &Hand<DRAW_HAND_SIZE>) -> Holds;
+}
+
+impl<F> DiscardStrategy for F
+where
+    F: Fn(&Hand<DRAW_HAND_SIZE>) -> Holds + Sync,
+{
+    fn hold(&self, hand: &Hand<DRAW_HAND_SIZE>) -> Holds {
+        self(hand)
+    }
+}
+
+/// Returns true if the hand is a straight or better, in which case there is
+/// nothing to gain by drawing.
+fn is_pat_hand(hand: &Hand<DRAW_HAND_SIZE>) -> bool {
+    HandValue::from(hand).category() >= HandCategory::Straight
+}
+
+/// Keep every card that makes up a pair or better, and draw to the rest.
+/// With `keep_kicker`, a lone pair also keeps its highest other card (so
+/// draws two instead of three). Pat hands are never broken up.
+#[derive(Default, Debug, Clone, Copy)]
+pub struct KeepPairs {
+    pub keep_kicker: bool,
+}
+
+impl DiscardStrategy for KeepPairs {
+    fn hold(&self, hand: &Hand<DRAW_HAND_SIZE>) -> Holds {
+        if is_pat_hand(hand) {
+            return [true; DRAW_HAND_SIZE];
+        }
+        let stats = HandStats::from(hand);
+        let mut holds = hand
+            .cards
+            .map(|card| stats.rank_count[card.rank().index()] >= 2);
+        let num_held = holds.iter().filter(|held| **held).count();
+        if self.keep_kicker && num_held == 2 {
+            let kicker = (0..DRAW_HAND_SIZE)
+                .filter(|i| !holds[*i])
+                .max_by_key(|i| ace_high_index(hand.cards[*i].rank()));
+            if let Some(i) = kicker {
+                holds[i] = true;
+            }
+        }
+        holds
+    }
+}
+
+/// Keep four cards of the same suit and draw one to the flush. Hands without
+/// a four flush are played the same way as [`KeepPairs`].
+#[derive(Default, Debug, Clone, Copy)]
+pub struct DrawToFlush;
+
+impl DiscardStrategy for DrawToFlush {
+    fn hold(&self, hand: &Hand<DRAW_HAND_SIZE>) -> Holds {
+        if is_pat_hand(hand) {
+            return [true; DRAW_HAND_SIZE];
+        }
+        let stats = HandStats::from(hand);
+        if let Some(suit) = Suit::iter().find(|suit| stats.suit_count[suit.index()] == 4) {
+            return hand.cards.map(|card| card.suit() == suit);
+        }
+        KeepPairs::default().hold(hand)
+    }
+}
+
+/// Deals `num_samples` five card hands, and plays each of them once with every
+/// strategy. All strategies see the same deal and draw from the same shuffled
+/// stub, which keeps the comparison between them as fair as possible. Returns
+/// the categories of the final hands, one entry per strategy.
+pub fn simulate_draw<R: Rng>(
+    rng: &mut R,
+    strategies: &[&dyn DiscardStrategy],
+    num_samples: u32,
+) -> Vec<AggregateScore> {
+    let mut scores: Vec<AggregateScore> = strategies.iter().map(|_| Default::default()).collect();
+    for _ in 0..num_samples {
+        let hand = Hand::<DRAW_HAND_SIZE>::draw(rng);
+        let mut stub = remaining_cards(&hand.cards);
+        let (replacements, _) = stub.partial_shuffle(rng, DRAW_HAND_SIZE);
+        for (strategy, score) in strategies.iter().zip(scores.iter_mut()) {
+            let holds = strategy.hold(&hand);
+            let mut replacements = replacements.iter();
+            let final_hand = Hand::<DRAW_HAND_SIZE> {
+                cards: array_init::array_init(|i| {
+                    if holds[i] {
+                        hand.cards[i]
+                    } else {
+                        *replacements.next().unwrap()
+                    }
+                }),
+            };
+            score.insert(&HandScore::from(&final_hand));
+        }
+    }
+    scores
+}
+
+/// Multi-threaded version of `simulate_draw`, with one batch of deals per
+/// thread. The same `seed` and number of threads deal the same hands.
+pub fn parallel_simulate_draw(
+    strategies: &[&dyn DiscardStrategy],
+    num_samples: u32,
+    num_threads: u32,
+    seed: u64,
+) -> Result<Vec<AggregateScore>> {
+    parallel_batches(
+        num_samples,
+        num_threads,
+        vec![AggregateScore::default(); strategies.len()],
+        |batch, batch_size| {
+            Ok(simulate_draw(
+                &mut batch_rng(seed, batch),
+                strategies,
+                batch_size,
+            ))
+        },
+        |scores: &mut Vec<AggregateScore>, batch_scores| {
+            for (score, batch_score) in scores.iter_mut().zip(batch_scores.iter()) {
+                score.insert(batch_score);
+            }
+        },
+    )
+}
+
+//////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use crate::deck::Deck;
+    use crate::draw_poker::parallel_simulate_draw;
+    use crate::draw_poker::simulate_draw;
+    use crate::draw_poker::DiscardStrategy;
+    use crate::draw_poker::DrawToFlush;
+    use crate::draw_poker::Holds;
+    use crate::draw_poker::KeepPairs;
+    use crate::hand::Hand;
+    use crate::hand_value::HandCategory;
+    use rand::SeedableRng;
+
+    #[test]
+    fn built_in_strategies() {
+        let deck = Deck::new();
+        let pair = deck.draw_hand(&["5♣", "8♣", "8♠", "A♣", "9♦"]).unwrap();
+        assert_eq!(
+            KeepPairs::default().hold(&pair),
+            [false, true, true, false, false]
+        );
+        assert_eq!(
+            KeepPairs { keep_kicker: true }.hold(&pair),
+            [false, true, true, true, false]
+        );
+        let four_flush = deck.draw_hand(&["5♣", "8♣", "8♠", "A♣", "9♣"]).unwrap();
+        assert_eq!(
+            DrawToFlush.hold(&four_flush),
+            [true, true, false, true, true]
+        );
+        let straight = deck.draw_hand(&["5♦", "9♠", "7♠", "8♦", "6♥"]).unwrap();
+        assert_eq!(DrawToFlush.hold(&straight), [true; 5]);
+        assert_eq!(KeepPairs::default().hold(&straight), [true; 5]);
+    }
+
+    #[test]
+    fn compare_strategies() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(51);
+        let stand_pat = |_: &Hand<5>| -> Holds { [true; 5] };
+        let draw_five = |_: &Hand<5>| -> Holds { [false; 5] };
+        let scores = simulate_draw(
+            &mut rng,
+            &[&stand_pat, &draw_five, &KeepPairs::default()],
+            20000,
+        );
+        assert_eq!(scores.len(), 3);
+        for score in &scores {
+            assert_eq!(score[HandCategory::HighCard], 20000);
+        }
+        // Keeping pairs can only make more pairs than a fresh hand, which is
+        // about half of the time.
+        assert!(scores[0][HandCategory::Pair] > 9000 && scores[0][HandCategory::Pair] < 11000);
+        assert!(scores[1][HandCategory::Pair] > 9000 && scores[1][HandCategory::Pair] < 11000);
+        assert!(scores[2][HandCategory::Pair] > scores[0][HandCategory::Pair] + 2000);
+        assert!(scores[2][HandCategory::ThreeOfAKind] > scores[0][HandCategory::ThreeOfAKind]);
+    }
+
+    #[test]
+    fn parallel_simulation() {
+        let scores = parallel_simulate_draw(&[&DrawToFlush], 1001, 4, 17).unwrap();
+        assert_eq!(scores.len(), 1);
+        assert_eq!(
+            scores,
+            parallel_simulate_draw(&[&DrawToFlush], 1001, 4, 17).unwrap()
+        );
+        assert_ne!(
+            scores,
+            parallel_simulate_draw(&[&DrawToFlush], 1001, 4, 18).unwrap()
+        );
+        assert_eq!(scores[0][HandCategory::HighCard], 1001);
+    }
+}
@@ -0,0 +1,298 @@
+use crate::card::Card;
+use crate::deck::remaining_cards;
+use crate::error::{PokerError, Result};
+use crate::hand_value::HandValue;
+use crate::holdem::{validate_street_hand, BOARD_SIZE, HOLE_CARDS};
+use crate::parallel::prelude::*;
+use crate::preflop::{StartingHand, NUM_STARTING_HANDS};
+use itertools::Itertools;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+pub const DEFAULT_NUM_BINS: usize = 50;
+
+/// How the river equity of a hand against one random opponent is spread out
+/// over the boards that can still come. Bin `i` counts the boards with an
+/// equity from `i / num_bins` up to `(i + 1) / num_bins`, where the last bin
+/// also holds an equity of exactly one.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct EquityHistogram {
+    pub counts: Vec<u64>,
+}
+
+impl EquityHistogram {
+    /// An empty histogram. Fails if there are no bins.
+    pub fn new(num_bins: usize) -> Result<EquityHistogram> {
+        if num_bins == 0 {
+            return Err(PokerError::Zero("bins"));
+        }
+        Ok(EquityHistogram {
+            counts: vec![0; num_bins],
+        })
+    }
+
+    pub fn insert(&mut self, equity: f64) {
+        let num_bins = self.counts.len();
+        let bin = ((equity * num_bins as f64) as usize).min(num_bins - 1);
+        self.counts[bin] += 1;
+    }
+
+    pub fn num_boards(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// Share of the boards in each bin.
+    pub fn fractions(&self) -> Vec<f64> {
+        let num_boards = self.num_boards() as f64;
+        self.counts
+            .iter()
+            .map(|count| *count as f64 / num_boards)
+            .collect()
+    }
+
+    /// Mean equity, taking each board at the middle of its bin.
+    pub fn mean(&self) -> f64 {
+        let num_bins = self.counts.len() as f64;
+        self.fractions()
+            .iter()
+            .enumerate()
+            .map(|(bin, fraction)| fraction * (bin as f64 + 0.5) / num_bins)
+            .sum()
+    }
+}
+
+/// Earth mover's distance between two histograms with the same bins: the
+/// least equity that has to move to turn one distribution into the other.
+/// For one dimension, this is the area between the two cumulative
+/// distributions. Returns `None` if the number of bins is different.
+pub fn earth_movers_distance(a: &EquityHistogram, b: &EquityHistogram) -> Option<f64> {
+    if a.counts.len() != b.counts.len() {
+        return None;
+    }
+    let bin_width = 1.0 / a.counts.len() as f64;
+    let mut difference = 0.0;
+    let mut distance = 0.0;
+    for (fraction_a, fraction_b) in a.fractions().iter().zip(b.fractions().iter()) {
+        difference += fraction_a - fraction_b;
+        distance += difference.abs() * bin_width;
+    }
+    Some(distance)
+}
+
+/// Equity of the hole cards on a complete board against every opponent
+/// holding, with ties counting as half.
+fn river_equity(hole_cards: &[Card], board: &[Card]) -> f64 {
+    let mut cards: Vec<Card> = board.to_vec();
+    cards.extend_from_slice(hole_cards);
+    let hero = HandValue::from_cards(&cards);
+    let known: Vec<Card> = hole_cards.iter().chain(board.iter()).copied().collect();
+    let mut wins = 0.0;
+    let mut num_opponents = 0;
+    for opponent in remaining_cards(&known).into_iter().combinations(HOLE_CARDS) {
+        cards.truncate(board.len());
+        cards.extend_from_slice(&opponent);
+        let villain = HandValue::from_cards(&cards);
+        wins += match hero.cmp(&villain) {
+            std::cmp::Ordering::Greater => 1.0,
+            std::cmp::Ordering::Equal => 0.5,
+            std::cmp::Ordering::Less => 0.0,
+        };
+        num_opponents += 1;
+    }
+    wins / num_opponents as f64
+}
+
+/// Bins the river equity of the hole cards over each of the completed boards
+/// into `histogram`, with the boards split across threads.
+fn histogram_for_boards(
+    hole_cards: &[Card],
+    boards: Vec<Vec<Card>>,
+    mut histogram: EquityHistogram,
+) -> EquityHistogram {
+    let equities: Vec<f64> = boards
+        .into_par_iter()
+        .map(|board| river_equity(hole_cards, &board))
+        .collect();
+    for equity in equities {
+        histogram.insert(equity);
+    }
+    histogram
+}
+
+/// Exact equity histogram of the hole cards, over every way to complete the
+/// board. Fails if the cards are not valid, there are no bins, or the board
+/// has fewer than three cards, where there are too many boards and sampling
+/// is needed.
+pub fn equity_histogram(
+    hole_cards: &[Card],
+    board: &[Card],
+    num_bins: usize,
+) -> Result<EquityHistogram> {
+    validate_street_hand(hole_cards, board)?;
+    if board.len() < 3 {
+        return Err(PokerError::InvalidCardCount {
+            what: "the board",
+            expected: "3 to 5",
+            found: board.len(),
+        });
+    }
+    let histogram = EquityHistogram::new(num_bins)?;
+    let known: Vec<Card> = hole_cards.iter().chain(board.iter()).copied().collect();
+    let boards = remaining_cards(&known)
+        .into_iter()
+        .combinations(BOARD_SIZE - board.len())
+        .map(|dealt| board.iter().chain(dealt.iter()).copied().collect())
+        .collect();
+    Ok(histogram_for_boards(hole_cards, boards, histogram))
+}
+
+/// Sampled version of `equity_histogram`, which also works before the flop.
+/// Each of the `num_boards` random boards is still scored exactly.
+pub fn sample_equity_histogram<R: Rng>(
+    rng: &mut R,
+    hole_cards: &[Card],
+    board: &[Card],
+    num_bins: usize,
+    num_boards: u32,
+) -> Result<EquityHistogram> {
+    validate_street_hand(hole_cards, board)?;
+    let histogram = EquityHistogram::new(num_bins)?;
+    let known: Vec<Card> = hole_cards.iter().chain(board.iter()).copied().collect();
+    let mut stub = remaining_cards(&known);
+    let boards = (0..num_boards)
+        .map(|_| {
+            let (dealt, _) = stub.partial_shuffle(rng, BOARD_SIZE - board.len());
+            board.iter().chain(dealt.iter()).copied().collect()
+        })
+        .collect();
+    Ok(histogram_for_boards(hole_cards, boards, histogram))
+}
+
+/// Sampled preflop histograms for all 169 starting hands, in index order.
+/// The suits of a starting hand don't change its histogram, so one of its
+/// combinations stands in for all of them. Fails if there are no bins.
+pub fn starting_hand_histograms<R: Rng>(
+    rng: &mut R,
+    num_bins: usize,
+    num_boards: u32,
+) -> Result<Vec<(StartingHand, EquityHistogram)>> {
+    (0..NUM_STARTING_HANDS)
+        .map(|index| {
+            let hand = StartingHand::from_index(index);
+            let hole_cards = hand.combos()[0];
+            let histogram = sample_equity_histogram(rng, &hole_cards, &[], num_bins, num_boards)?;
+            Ok((hand, histogram))
+        })
+        .collect()
+}
+
+/// Writes named histograms as CSV: a header with the middle equity of each
+/// bin, then one row per histogram with its name and the share of boards in
+/// each bin. All histograms must have the same number of bins.
+pub fn write_csv<W: Write, N: ToString>(
+    writer: &mut W,
+    histograms: &[(N, EquityHistogram)],
+) -> io::Result<()> {
+    let num_bins = histograms
+        .first()
+        .map_or(DEFAULT_NUM_BINS, |(_, histogram)| histogram.counts.len());
+    write!(writer, "name")?;
+    for bin in 0..num_bins {
+        write!(writer, ",{:.4}", (bin as f64 + 0.5) / num_bins as f64)?;
+    }
+    writeln!(writer)?;
+    for (name, histogram) in histograms {
+        if histogram.counts.len() != num_bins {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "histograms have different numbers of bins",
+            ));
+        }
+        write!(writer, "{}", name.to_string())?;
+        for fraction in histogram.fractions() {
+            write!(writer, ",{fraction:.6}")?;
+        }
+        writeln!(writer)?;
+    }
+    Ok(())
+}
+
+pub fn save_csv<P: AsRef<Path>, N: ToString>(
+    path: P,
+    histograms: &[(N, EquityHistogram)],
+) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    write_csv(&mut writer, histograms)?;
+    writer.flush()
+}
+
+//////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use crate::deck::Deck;
+    use crate::equity_histogram::earth_movers_distance;
+    use crate::equity_histogram::equity_histogram;
+    use crate::equity_histogram::sample_equity_histogram;
+    use crate::equity_histogram::starting_hand_histograms;
+    use crate::equity_histogram::write_csv;
+    use crate::equity_histogram::EquityHistogram;
+    use crate::error::PokerError;
+    use rand::SeedableRng;
+
+    #[test]
+    fn distances() {
+        let mut low = EquityHistogram::new(4).unwrap();
+        let mut high = EquityHistogram::new(4).unwrap();
+        low.insert(0.1);
+        high.insert(1.0);
+        assert_eq!(high.counts, vec![0, 0, 0, 1]);
+        // All the weight moves three bins of a quarter each.
+        assert!((earth_movers_distance(&low, &high).unwrap() - 0.75).abs() < 1e-12);
+        assert_eq!(earth_movers_distance(&low, &low), Some(0.0));
+        assert!(earth_movers_distance(&low, &EquityHistogram::new(5).unwrap()).is_none());
+        assert!(matches!(
+            EquityHistogram::new(0),
+            Err(PokerError::Zero("bins"))
+        ));
+        let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+        assert!(matches!(
+            starting_hand_histograms(&mut rng, 0, 10),
+            Err(PokerError::Zero("bins"))
+        ));
+    }
+
+    /// On the turn, a made flush is spread near the top, while a flush draw
+    /// either gets there or ends up near the bottom.
+    #[test]
+    fn turn_histograms() {
+        let deck = Deck::new();
+        let board = deck.draw_cards(&["2♥", "7♥", "9♣", "J♥"]).unwrap();
+        let flush = deck.draw_cards(&["A♥", "K♥"]).unwrap();
+        let draw = deck.draw_cards(&["A♥", "K♣"]).unwrap();
+        let made = equity_histogram(&flush, &board, 10).unwrap();
+        let drawing = equity_histogram(&draw, &board, 10).unwrap();
+        assert_eq!(made.num_boards(), 46);
+        assert!(made.mean() > 0.9);
+        assert!(drawing.counts[9] >= 8 && drawing.mean() < made.mean());
+        assert!(earth_movers_distance(&made, &drawing).unwrap() > 0.2);
+        assert!(equity_histogram(&flush, &[], 10).is_err());
+        assert!(equity_histogram(&flush, &board, 0).is_err());
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(40);
+        let sampled = sample_equity_histogram(&mut rng, &flush, &board, 10, 400).unwrap();
+        assert!(earth_movers_distance(&made, &sampled).unwrap() < 0.02);
+
+        let mut csv = Vec::new();
+        write_csv(&mut csv, &[("flush", made), ("draw", drawing)]).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("name,0.0500,0.1500"));
+        assert!(lines[1].starts_with("flush,"));
+        assert_eq!(lines[2].split(',').count(), 11);
+    }
+}
@@ -0,0 +1,83 @@
+use crate::card::{OutOfRange, ParseCardError};
+use std::fmt;
+use std::io;
+
+/// Everything that can go wrong when parsing, dealing or evaluating cards.
+#[derive(Debug)]
+pub enum PokerError {
+    /// A string that is not the name of a card, such as "1h".
+    InvalidCardName(String),
+    /// A string that is not the name of a starting hand, such as "AKx".
+    InvalidStartingHand(String),
+    /// A number that is too large to be a rank, suit or card.
+    OutOfRange(u8),
+    /// The same card was dealt more than once.
+    DuplicateCards,
+    /// Some group of cards has the wrong size, such as a board of two cards.
+    InvalidCardCount {
+        what: &'static str,
+        expected: &'static str,
+        found: usize,
+    },
+    /// A table without any players.
+    NoPlayers,
+    /// There are not enough cards left in the deck to finish the hand.
+    NotEnoughCards,
+    /// A count that must be at least one, such as the number of threads.
+    Zero(&'static str),
+    Io(io::Error),
+}
+
+/// Result type of the fallible functions in this crate.
+pub type Result<T> = std::result::Result<T, PokerError>;
+
+impl fmt::Display for PokerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PokerError::InvalidCardName(name) => write!(f, "invalid card name: {name:?}"),
+            PokerError::InvalidStartingHand(name) => {
+                write!(f, "invalid starting hand: {name:?}")
+            }
+            PokerError::OutOfRange(value) => write!(f, "{value} is out of range"),
+            PokerError::DuplicateCards => write!(f, "every card must be different"),
+            PokerError::InvalidCardCount {
+                what,
+                expected,
+                found,
+            } => write!(f, "{what} must have {expected} cards, not {found}"),
+            PokerError::NoPlayers => write!(f, "there must be at least one player"),
+            PokerError::NotEnoughCards => {
+                write!(f, "not enough cards left in the deck to finish the hand")
+            }
+            PokerError::Zero(what) => write!(f, "the number of {what} must be at least one"),
+            PokerError::Io(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for PokerError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PokerError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for PokerError {
+    fn from(error: io::Error) -> PokerError {
+        PokerError::Io(error)
+    }
+}
+
+impl From<OutOfRange> for PokerError {
+    fn from(error: OutOfRange) -> PokerError {
+        PokerError::OutOfRange(error.0)
+    }
+}
+
+impl From<ParseCardError> for PokerError {
+    fn from(error: ParseCardError) -> PokerError {
+        PokerError::InvalidCardName(error.0)
+    }
+}
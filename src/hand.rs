@@ -1,49 +1,132 @@
 use crate::card::Card;
+use crate::card_subsets::binomial;
+use crate::error::{PokerError, Result};
 use itertools::Itertools;
 use rand::Rng;
 use std::fmt;
 
-#[derive(PartialEq, Debug, Hash)]
+/// Hands compare card by card, so two hands with the same cards are only
+/// equal when the cards are in the same order. Use [`Hand::sorted`] to get
+/// a canonical form, for example before using a hand as a map key.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Copy, Clone, Hash)]
 pub struct Hand<const N: usize> {
     pub cards: [Card; N],
 }
 
-impl<const N: usize> Hand<N> {
-    /// Check for duplicates in the array, starting with `start_index`.
-    /// Return the index of the first duplicate found.
-    /// The `start_index` parameter allows the search to resume after replacing
-    /// a duplicate entry.
-    fn check_for_duplicates(&self, start_index: usize) -> Option<usize> {
-        for i in start_index..N {
-            for j in 0..i {
-                if self.cards[i] == self.cards[j] {
-                    return Some(i);
-                }
+/// Check for duplicates in the array, starting with `start_index`.
+/// Return the index of the first duplicate found.
+/// The `start_index` parameter allows the search to resume after replacing
+/// a duplicate entry.
+fn check_for_duplicates<T: PartialEq>(items: &[T], start_index: usize) -> Option<usize> {
+    for i in start_index..items.len() {
+        for j in 0..i {
+            if items[i] == items[j] {
+                return Some(i);
             }
         }
-        None
     }
+    None
+}
 
+impl<const N: usize> Hand<N> {
     /// Returns an array of N cards that are sampled from the deck without
-    /// replacement Note: this algorithm is efficient for small N, but is very
-    /// slow as N approaches Card::NUM_CARDS. For values larger than
-    /// (Card::NUM_CARDS-1) it will block forever. For now, this is private to
-    /// the module so that it can only be called when N << Card::NUM_CARDS.
+    /// replacement. Note: this algorithm is efficient for small N, but is very
+    /// slow as N approaches Card::NUM_CARDS, so only call it when
+    /// N << Card::NUM_CARDS. For N larger than Card::NUM_CARDS it will block
+    /// forever.
     pub fn draw<R: Rng>(rng: &mut R) -> Hand<N> {
-        // Draw N cards with replacement
-        let mut hand = Hand {
-            cards: array_init::array_init(|_| Card::draw_random_card(rng)),
-        };
+        Hand::draw_unchecked(rng, &[], 1)
+    }
+
+    /// Returns an array of N cards that are sampled without replacement from a
+    /// shoe that contains `num_decks` standard decks shuffled together. Each
+    /// physical card in the shoe is drawn at most once, but the same [`Card`]
+    /// may appear up to `num_decks` times in the hand. The same caveats as
+    /// [`Hand::draw`] apply: N must be much smaller than the size of the shoe.
+    /// Fails if there are no decks, or fewer than N cards in the shoe.
+    pub fn draw_from_shoe<R: Rng>(rng: &mut R, num_decks: usize) -> Result<Hand<N>> {
+        Hand::draw_from_shoe_with_known(rng, &[], num_decks)
+    }
+
+    /// Same as [`Hand::draw_from_shoe`], for hands that start with the
+    /// `known` cards. The rest of the hand is drawn from the shoe without
+    /// them. Also fails if the known cards don't fit in the shoe (see
+    /// [`check_fit_in_shoe`]) or in the hand.
+    pub fn draw_from_shoe_with_known<R: Rng>(
+        rng: &mut R,
+        known: &[Card],
+        num_decks: usize,
+    ) -> Result<Hand<N>> {
+        check_known::<N>(known, num_decks)?;
+        Ok(Hand::draw_unchecked(rng, known, num_decks))
+    }
+
+    /// [`Hand::draw_from_shoe_with_known`] without checking the input, for
+    /// callers that have already passed it to [`check_known`] and draw many
+    /// hands.
+    pub(crate) fn draw_unchecked<R: Rng>(rng: &mut R, known: &[Card], num_decks: usize) -> Hand<N> {
+        let shoe_size = num_decks * Card::NUM_CARDS;
+        // The known cards take the first copies of their cards in the shoe,
+        // and the rest are drawn with replacement.
+        let mut copies_known = [0; Card::NUM_CARDS];
+        let mut shoe_indices: [usize; N] = array_init::array_init(|i| match known.get(i) {
+            Some(card) => {
+                copies_known[card.index()] += 1;
+                card.index() + (copies_known[card.index()] - 1) * Card::NUM_CARDS
+            }
+            None => rng.gen_range(0..shoe_size),
+        });
         // Replace any duplicates.
-        let mut start_index: usize = 1;
-        while let Some(i) = hand.check_for_duplicates(start_index) {
-            hand.cards[i] = Card::draw_random_card(rng);
+        let mut start_index: usize = known.len().max(1);
+        while let Some(i) = check_for_duplicates(&shoe_indices, start_index) {
+            shoe_indices[i] = rng.gen_range(0..shoe_size);
             start_index = i;
         }
+        Hand {
+            cards: array_init::array_init(|i| Card::ALL[shoe_indices[i] % Card::NUM_CARDS]),
+        }
+    }
+
+    /// Puts the cards in canonical order: from the highest rank down, with
+    /// the ace high, and by suit from spades down within a rank.
+    pub fn sort(&mut self) {
+        self.cards.sort_by(|a, b| b.cmp(a));
+    }
+
+    /// Copy of the hand with the cards in canonical order, see [`Hand::sort`].
+    pub fn sorted(&self) -> Hand<N> {
+        let mut hand = *self;
+        hand.sort();
         hand
     }
 }
 
+/// Hands are written as a list of cards, such as `["Ah", "Kd"]`.
+#[cfg(feature = "serde")]
+impl<const N: usize> serde::Serialize for Hand<N> {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.cards.iter())
+    }
+}
+
+/// Reads a list of exactly N cards. The cards are kept in the order given.
+#[cfg(feature = "serde")]
+impl<'de, const N: usize> serde::Deserialize<'de> for Hand<N> {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Hand<N>, D::Error> {
+        let cards = Vec::<Card>::deserialize(deserializer)?;
+        let num_cards = cards.len();
+        let cards: [Card; N] = cards.try_into().map_err(|_| {
+            serde::de::Error::invalid_length(num_cards, &format!("{N} cards").as_str())
+        })?;
+        Ok(Hand { cards })
+    }
+}
+
 impl<const N: usize> fmt::Display for Hand<N> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let string = self
@@ -56,11 +139,127 @@ impl<const N: usize> fmt::Display for Hand<N> {
     }
 }
 
-#[allow(dead_code)]
+/// Checks that no card appears twice.
+pub fn check_unique(cards: &[Card]) -> Result<()> {
+    let mut sorted = cards.to_vec();
+    sorted.sort();
+    if sorted.windows(2).any(|pair| pair[0] == pair[1]) {
+        Err(PokerError::DuplicateCards)
+    } else {
+        Ok(())
+    }
+}
+
 pub fn cards_are_unique<const N: usize>(hand: &Hand<N>) -> bool {
-    // There are probably more efficient ways to do this for small N, but this
-    // is certainly the least typing, and fast enough for simple unit tests.
-    hand.cards.into_iter().unique().count() == N
+    check_unique(&hand.cards).is_ok()
+}
+
+/// Checks that the cards could have been dealt from a shoe of `num_decks`
+/// decks, i.e. no card appears more than `num_decks` times.
+pub fn check_fit_in_shoe(cards: &[Card], num_decks: usize) -> Result<()> {
+    if cards
+        .iter()
+        .counts()
+        .values()
+        .all(|&count| count <= num_decks)
+    {
+        Ok(())
+    } else {
+        Err(PokerError::DuplicateCards)
+    }
+}
+
+/// Checks that there is at least one deck, and that the `known` cards fit in
+/// the shoe and in a hand of N cards.
+pub(crate) fn check_known<const N: usize>(known: &[Card], num_decks: usize) -> Result<()> {
+    if num_decks == 0 {
+        return Err(PokerError::Zero("decks"));
+    }
+    if known.len() > N {
+        return Err(PokerError::InvalidCardCount {
+            what: "the known cards",
+            expected: "no more than the hand's",
+            found: known.len(),
+        });
+    }
+    if N > num_decks * Card::NUM_CARDS {
+        return Err(PokerError::NotEnoughCards);
+    }
+    check_fit_in_shoe(known, num_decks)
+}
+
+/// Returns true if the hand could have been dealt from a shoe of `num_decks`
+/// decks, see [`check_fit_in_shoe`].
+pub fn cards_fit_in_shoe<const N: usize>(hand: &Hand<N>, num_decks: usize) -> bool {
+    check_fit_in_shoe(&hand.cards, num_decks).is_ok()
+}
+
+/// Visit every distinct hand of N cards that can be dealt from a shoe of
+/// `num_decks` decks. Hands are visited once per multiset of cards, in sorted
+/// card order, along with the number of ways that multiset can be dealt from
+/// the physical cards in the shoe. The weights sum to C(52 * num_decks, N).
+pub fn for_each_hand_in_shoe<const N: usize, F>(num_decks: usize, func: F)
+where
+    F: FnMut(&Hand<N>, u64),
+{
+    for_each_hand_in_shoe_with_known(&[], num_decks, func);
+}
+
+/// Same as [`for_each_hand_in_shoe`], for hands that start with the `known`
+/// cards, followed by every multiset of the other cards that can be dealt
+/// from the rest of the shoe. The weights sum to C(52 * num_decks - K, N - K)
+/// for K known cards, which must fit in the shoe (see [`check_fit_in_shoe`])
+/// and in the hand.
+pub fn for_each_hand_in_shoe_with_known<const N: usize, F>(
+    known: &[Card],
+    num_decks: usize,
+    mut func: F,
+) where
+    F: FnMut(&Hand<N>, u64),
+{
+    let mut hand = Hand {
+        cards: [Card::ALL[0]; N],
+    };
+    let mut available = [num_decks; Card::NUM_CARDS];
+    for (slot, card) in hand.cards.iter_mut().zip(known) {
+        *slot = *card;
+        available[card.index()] = available[card.index()].saturating_sub(1);
+    }
+    enumerate_from(&mut hand, known.len(), 0, &available, 1, &mut func);
+}
+
+/// Recursive helper for [`for_each_hand_in_shoe_with_known`]: fill
+/// `hand.cards[index..]` using cards with id of at least `first_id`, and at
+/// most `available` copies of each card.
+fn enumerate_from<const N: usize, F>(
+    hand: &mut Hand<N>,
+    index: usize,
+    first_id: usize,
+    available: &[usize; Card::NUM_CARDS],
+    weight: u64,
+    func: &mut F,
+) where
+    F: FnMut(&Hand<N>, u64),
+{
+    if index >= N {
+        func(hand, weight);
+        return;
+    }
+    for id in first_id..Card::NUM_CARDS {
+        // Place `copies` of this card, then move on to strictly larger ids.
+        let max_copies = available[id].min(N - index);
+        for copies in 1..=max_copies {
+            hand.cards[index + copies - 1] = Card::ALL[id];
+            enumerate_from(
+                hand,
+                index + copies,
+                id + 1,
+                available,
+                weight * binomial(available[id], copies) as u64,
+                func,
+            );
+        }
+    }
 }
 
 //////////////////////////////////////////////////////////////////////////////////////
@@ -68,9 +267,17 @@ pub fn cards_are_unique<const N: usize>(hand: &Hand<N>) -> bool {
 #[cfg(test)]
 mod tests {
 
+    use crate::deck::Deck;
+    use crate::error::PokerError;
     use crate::hand::cards_are_unique;
+    use crate::hand::cards_fit_in_shoe;
+    use crate::hand::check_fit_in_shoe;
+    use crate::hand::check_unique;
+    use crate::hand::for_each_hand_in_shoe;
+    use crate::hand::for_each_hand_in_shoe_with_known;
     use crate::hand::Hand;
     use rand::SeedableRng;
+    use std::collections::HashMap;
 
     /// Ensure that cards within a single hand are unique.
     #[test]
@@ -84,4 +291,142 @@ mod tests {
             assert!(cards_are_unique(&hand));
         }
     }
+
+    /// Hands drawn from a multi-deck shoe may repeat cards, but never more
+    /// often than there are decks in the shoe.
+    #[test]
+    fn cards_drawn_from_shoe_fit_in_shoe_test() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(882211);
+
+        let mut found_duplicate = false;
+        for _ in 0..2000 {
+            let hand = Hand::<7>::draw_from_shoe(&mut rng, 2).unwrap();
+            assert!(cards_fit_in_shoe(&hand, 2));
+            found_duplicate |= !cards_are_unique(&hand);
+        }
+        assert!(found_duplicate);
+    }
+
+    #[test]
+    fn enumerate_hands_in_shoe_test() {
+        let mut count = 0;
+        let mut total_weight = 0;
+        for_each_hand_in_shoe::<3, _>(1, |hand, weight| {
+            assert!(cards_are_unique(hand));
+            count += 1;
+            total_weight += weight;
+        });
+        assert_eq!(count, 22100);
+        assert_eq!(total_weight, 22100);
+
+        // C(104, 3) ways to deal three cards from two decks.
+        let mut total_weight = 0;
+        for_each_hand_in_shoe::<3, _>(2, |hand, weight| {
+            assert!(cards_fit_in_shoe(hand, 2));
+            total_weight += weight;
+        });
+        assert_eq!(total_weight, 182104);
+    }
+
+    /// Hands with known cards start with them, and deal the rest from what
+    /// is left in the shoe.
+    #[test]
+    fn known_cards_in_shoe_test() {
+        let ace = Deck::new().draw_cards(&["A♠"]).unwrap()[0];
+        let mut count = 0;
+        let mut total_weight = 0;
+        for_each_hand_in_shoe_with_known::<3, _>(&[ace], 1, |hand, weight| {
+            assert_eq!(hand.cards[0], ace);
+            assert!(cards_are_unique(hand));
+            count += 1;
+            total_weight += weight;
+        });
+        assert_eq!(count, 1275);
+        assert_eq!(total_weight, 1275);
+
+        // Both aces of spades in a two deck shoe leave 102 other cards.
+        let mut total_weight = 0;
+        for_each_hand_in_shoe_with_known::<3, _>(&[ace, ace], 2, |hand, weight| {
+            assert_eq!(hand.cards[..2], [ace, ace]);
+            assert_ne!(hand.cards[2], ace);
+            total_weight += weight;
+        });
+        assert_eq!(total_weight, 102);
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(4471);
+        for _ in 0..500 {
+            let hand = Hand::<7>::draw_from_shoe_with_known(&mut rng, &[ace, ace], 2).unwrap();
+            assert_eq!(hand.cards[..2], [ace, ace]);
+            assert!(cards_fit_in_shoe(&hand, 2));
+            let hand = Hand::<5>::draw_from_shoe_with_known(&mut rng, &[ace], 1).unwrap();
+            assert_eq!(hand.cards[0], ace);
+            assert!(cards_are_unique(&hand));
+        }
+    }
+
+    #[test]
+    fn invalid_shoes() {
+        let ace = Deck::new().draw_cards(&["A♠"]).unwrap()[0];
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+        assert!(matches!(
+            Hand::<5>::draw_from_shoe(&mut rng, 0),
+            Err(PokerError::Zero("decks"))
+        ));
+        assert!(matches!(
+            Hand::<53>::draw_from_shoe(&mut rng, 1),
+            Err(PokerError::NotEnoughCards)
+        ));
+        assert!(matches!(
+            Hand::<2>::draw_from_shoe_with_known(&mut rng, &[ace; 3], 3),
+            Err(PokerError::InvalidCardCount { found: 3, .. })
+        ));
+        assert!(matches!(
+            Hand::<5>::draw_from_shoe_with_known(&mut rng, &[ace, ace], 1),
+            Err(PokerError::DuplicateCards)
+        ));
+    }
+
+    #[test]
+    fn duplicate_cards() {
+        let deck = Deck::new();
+        let cards = deck.draw_cards(&["A♠", "K♦", "A♠"]).unwrap();
+        assert!(matches!(
+            check_unique(&cards),
+            Err(PokerError::DuplicateCards)
+        ));
+        assert!(check_unique(&cards[1..]).is_ok());
+        assert!(check_unique(&[]).is_ok());
+        assert!(check_fit_in_shoe(&cards, 2).is_ok());
+        assert!(matches!(
+            check_fit_in_shoe(&cards, 1),
+            Err(PokerError::DuplicateCards)
+        ));
+    }
+
+    #[test]
+    fn sorted_hands_as_keys() {
+        let deck = Deck::new();
+        let hand = deck.draw_hand(&["7♦", "A♣", "7♠", "2♥", "K♦"]).unwrap();
+        let sorted = hand.sorted();
+        assert_eq!(sorted.to_string(), "Hand: A♣, K♦, 7♠, 7♦, 2♥");
+        assert_eq!(sorted.sorted(), sorted);
+
+        let mut counts: HashMap<Hand<5>, u32> = HashMap::new();
+        let shuffled = deck.draw_hand(&["K♦", "2♥", "7♠", "A♣", "7♦"]).unwrap();
+        for hand in [hand, shuffled] {
+            *counts.entry(hand.sorted()).or_default() += 1;
+        }
+        assert_eq!(counts.len(), 1);
+        assert_eq!(counts[&sorted], 2);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_hands() {
+        let hand = Deck::new().draw_hand(&["A♥", "T♣", "2♦"]).unwrap();
+        let json = serde_json::to_string(&hand).unwrap();
+        assert_eq!(json, r#"["Ah","Tc","2d"]"#);
+        assert_eq!(serde_json::from_str::<Hand<3>>(&json).unwrap(), hand);
+        assert!(serde_json::from_str::<Hand<2>>(&json).is_err());
+    }
 }
@@ -1,22 +1,20 @@
+use crate::card::{Rank, Suit};
 use crate::hand::Hand;
-use crate::{deck::Deck, hand::cards_are_unique, hand_stats::HandStats};
+use crate::hand_stats::HandStats;
+use crate::hand_value::HandCategory;
+use std::ops::{Index, IndexMut};
 
-#[derive(Default, PartialEq, Debug)]
+/// A count for every [`HandCategory`], indexed by the category. The last two
+/// categories can only occur when dealing from a shoe that contains more than
+/// one deck: five of a kind needs five cards of one rank, and flush five needs
+/// five copies of the same card.
+#[derive(Default, PartialEq, Debug, Clone)]
 pub struct HandData {
-    pub high_card: u32,
-    pub pair: u32,
-    pub two_pair: u32,
-    pub three_of_a_kind: u32,
-    pub straight: u32,
-    pub flush: u32,
-    pub full_house: u32,
-    pub four_of_a_kind: u32,
-    pub straight_flush: u32,
+    counts: [u64; HandCategory::NUM_CATEGORIES],
 }
 
 pub type HandScore = HandData;
 
-#[allow(dead_code)]
 pub fn is_flush(hand_stats: &HandStats) -> bool {
     for count in hand_stats.suit_count {
         if count >= 5 {
@@ -26,116 +24,239 @@ pub fn is_flush(hand_stats: &HandStats) -> bool {
     false
 }
 
-/// Check to see if there is a straight. Must work for both
-/// five and seven card hands, so it is a bit less optimized
-/// than it could be for a strictly five card hand.
-#[allow(dead_code)]
-pub fn is_straight(hand_stats: &HandStats) -> bool {
+/// Check for five consecutive ranks, where `has_rank` reports whether a rank
+/// (by id, ace is zero) is present. The ace is checked again after the king,
+/// so that it can play either low or high.
+fn has_five_in_a_row<F>(has_rank: F) -> bool
+where
+    F: Fn(usize) -> bool,
+{
     let mut cards_in_straight = 0;
-    for count in hand_stats.rank_count {
-        if count > 0 {
-            cards_in_straight = cards_in_straight + 1;
-        } else {
-            if cards_in_straight > 0 {
-                // We were in a straight... but found a gap.
-                return false;
+    for rank_id in 0..=Rank::NUM_RANKS {
+        if has_rank(rank_id % Rank::NUM_RANKS) {
+            cards_in_straight += 1;
+            if cards_in_straight >= 5 {
+                // Allow for early exit
+                return true;
             }
-        }
-        if cards_in_straight >= 5 {
-            // Allow for early exit
-            return true;
+        } else {
+            // Found a gap, so the next straight must start after it.
+            cards_in_straight = 0;
         }
     }
-    cards_in_straight >= 5
+    false
+}
+
+/// Check to see if there is a straight. Must work for both
+/// five and seven card hands, so it is a bit less optimized
+/// than it could be for a strictly five card hand.
+pub fn is_straight(hand_stats: &HandStats) -> bool {
+    has_five_in_a_row(|rank_id| hand_stats.rank_count[rank_id] > 0)
+}
+
+/// A straight flush needs the straight to be made from cards of one suit,
+/// which matters for seven card hands that contain both a straight and a flush.
+pub fn is_straight_flush(hand_stats: &HandStats) -> bool {
+    (0..Suit::NUM_SUITS).any(|suit_id| {
+        hand_stats.suit_count[suit_id] >= 5
+            && has_five_in_a_row(|rank_id| hand_stats.card_count[rank_id][suit_id] > 0)
+    })
+}
+
+/// A full house needs three cards of one rank and at least two of another.
+/// In a seven card hand the "pair" may be a second three of a kind.
+pub fn is_full_house(hand_stats: &HandStats) -> bool {
+    let triples = hand_stats.rank_count.iter().filter(|&&c| c >= 3).count();
+    let pairs = hand_stats.rank_count.iter().filter(|&&c| c == 2).count();
+    triples >= 2 || (triples == 1 && pairs >= 1)
 }
 
 impl HandScore {
-    fn populate_simple_multiples(&mut self, hand_stats: &HandStats) -> () {
+    fn populate_simple_multiples(&mut self, hand_stats: &HandStats) {
         for count in hand_stats.rank_count {
             match count {
                 2 => {
-                    if self.pair != 0 {
-                        self.two_pair = 1;
+                    if self[HandCategory::Pair] != 0 {
+                        self[HandCategory::TwoPair] = 1;
                     } else {
-                        self.pair = 1;
+                        self[HandCategory::Pair] = 1;
                     }
                 }
-                3 => self.three_of_a_kind = 1,
-                4 => self.four_of_a_kind = 1,
+                3 => self[HandCategory::ThreeOfAKind] = 1,
+                4 => self[HandCategory::FourOfAKind] = 1,
+                5.. => self[HandCategory::FiveOfAKind] = 1,
                 _ => (),
             }
         }
     }
 
-    fn populate_derived_scores(&mut self) -> () {
-        self.full_house = (self.pair != 0 && self.three_of_a_kind != 0) as u32;
-        if self.four_of_a_kind != 0 {
-            self.three_of_a_kind = 1;
+    fn populate_derived_scores(&mut self) {
+        // Each category implies the next one, in this order.
+        for (category, implied) in [
+            (HandCategory::FlushFive, HandCategory::FiveOfAKind),
+            (HandCategory::FiveOfAKind, HandCategory::FourOfAKind),
+            (HandCategory::FourOfAKind, HandCategory::ThreeOfAKind),
+            (HandCategory::ThreeOfAKind, HandCategory::Pair),
+        ] {
+            if self[category] != 0 {
+                self[implied] = 1;
+            }
         }
-        if self.three_of_a_kind != 0 {
-            self.pair = 1;
+    }
+}
+
+impl Index<HandCategory> for HandData {
+    type Output = u64;
+
+    fn index(&self, category: HandCategory) -> &u64 {
+        &self.counts[category.index()]
+    }
+}
+
+impl IndexMut<HandCategory> for HandData {
+    fn index_mut(&mut self, category: HandCategory) -> &mut u64 {
+        &mut self.counts[category.index()]
+    }
+}
+
+impl HandData {
+    /// A count of one for each of the `categories`, and zero for the rest.
+    pub fn from_categories(categories: &[HandCategory]) -> HandData {
+        let mut hand_data = HandData::default();
+        for category in categories {
+            hand_data[*category] = 1;
+        }
+        hand_data
+    }
+
+    /// Every category and its count, from `HighCard` to `FlushFive`.
+    pub fn iter(&self) -> impl Iterator<Item = (HandCategory, u64)> + '_ {
+        HandCategory::ALL
+            .iter()
+            .map(|category| (*category, self[*category]))
+    }
+
+    /// A score with only the best category that this score has, so that for
+    /// example a full house no longer also counts as a pair.
+    pub fn best_category_only(&self) -> HandScore {
+        let best = self.iter().filter(|(_, count)| *count != 0).last();
+        match best {
+            Some((category, _)) => HandScore::from_categories(&[category]),
+            None => HandScore::default(),
         }
-        self.straight_flush = (self.straight != 0 && self.flush != 0) as u32;
     }
 }
 
 pub fn display_hand_data<F>(hand_data: &HandData, object_name: &str, value_fmt: F) -> String
 where
-    F: Fn(u32) -> String,
+    F: Fn(u64) -> String,
 {
     let n_pad_name = "three_of_a_kind:".len();
-    let display_member = |name, value| format!("{:<n_pad_name$}  {}", name, value);
-    format!(
-        "{}: \n  {}\n  {}\n  {}\n  {}\n  {}\n  {}\n  {}\n  {}\n  {}",
-        object_name,
-        display_member("high_card", value_fmt(hand_data.high_card)),
-        display_member("pair", value_fmt(hand_data.pair)),
-        display_member("two_pair", value_fmt(hand_data.two_pair)),
-        display_member("three_of_a_kind", value_fmt(hand_data.three_of_a_kind)),
-        display_member("straight", value_fmt(hand_data.straight)),
-        display_member("flush", value_fmt(hand_data.flush)),
-        display_member("full_house", value_fmt(hand_data.full_house)),
-        display_member("four_of_a_kind", value_fmt(hand_data.four_of_a_kind)),
-        display_member("straight_flush", value_fmt(hand_data.straight_flush)),
-    )
+    let members: String = hand_data
+        .iter()
+        .map(|(category, value)| {
+            format!(
+                "\n  {:<n_pad_name$}  {}",
+                category.snake_case_name(),
+                value_fmt(value)
+            )
+        })
+        .collect();
+    format!("{}: {}", object_name, members)
+}
+
+/// Written as a map from the snake case name of each category to its count,
+/// such as `{"high_card": 1, "pair": 1, ...}`, so that saved data doesn't
+/// depend on the order of the categories.
+#[cfg(feature = "serde")]
+impl serde::Serialize for HandData {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(
+            self.iter()
+                .map(|(category, count)| (category.snake_case_name(), count)),
+        )
+    }
+}
+
+/// Reads the map written by `Serialize`. Categories that are missing count
+/// zero, and unknown category names are an error.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for HandData {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<HandData, D::Error> {
+        let counts = std::collections::HashMap::<std::borrow::Cow<'de, str>, u64>::deserialize(
+            deserializer,
+        )?;
+        let mut hand_data = HandData::default();
+        for (name, count) in counts {
+            let category = HandCategory::ALL
+                .into_iter()
+                .find(|category| category.snake_case_name() == name)
+                .ok_or_else(|| {
+                    serde::de::Error::custom(format!("unknown hand category: {name:?}"))
+                })?;
+            hand_data[category] = count;
+        }
+        Ok(hand_data)
+    }
 }
 
 impl From<&HandStats> for HandScore {
-    #[allow(dead_code)]
     fn from(hand_stats: &HandStats) -> HandScore {
-        let mut hand_scores: HandScore = Default::default();
-        hand_scores.high_card = 1;
-        hand_scores.flush = is_flush(hand_stats) as u32;
+        let mut hand_scores = HandScore::from_categories(&[HandCategory::HighCard]);
+        for (category, is_made) in [
+            (HandCategory::Flush, is_flush(hand_stats)),
+            (HandCategory::Straight, is_straight(hand_stats)),
+            (HandCategory::FullHouse, is_full_house(hand_stats)),
+            (HandCategory::StraightFlush, is_straight_flush(hand_stats)),
+            (
+                HandCategory::FlushFive,
+                hand_stats.max_duplicate_count() >= 5,
+            ),
+        ] {
+            hand_scores[category] = is_made as u64;
+        }
         hand_scores.populate_simple_multiples(hand_stats);
-        hand_scores.straight = is_straight(hand_stats) as u32;
         hand_scores.populate_derived_scores();
         hand_scores
     }
 }
 
 impl<const N: usize> From<&Hand<N>> for HandScore {
-    #[allow(dead_code)]
     fn from(hand: &Hand<N>) -> HandScore {
         HandScore::from(&HandStats::from(hand))
     }
 }
 
-#[allow(dead_code)]
-fn card_names_to_hand_score<const N: usize>(deck: &Deck, cards: &[&str; N]) -> HandScore {
-    let hand_opt = deck.draw_hand(cards);
-    // Check that the card names can be parsed:
-    assert!(hand_opt.is_some());
-    let hand = hand_opt.unwrap();
-    // Check that the test author gave a valid hand
-    assert!(cards_are_unique(&hand));
-    HandScore::from(&hand)
-}
-
 #[cfg(test)]
 mod tests {
     use crate::deck::Deck;
-    use crate::hand_score::card_names_to_hand_score;
+    use crate::hand::cards_fit_in_shoe;
+    use crate::hand::check_unique;
+    use crate::hand::for_each_hand_in_shoe;
+    use crate::hand_score::is_straight;
     use crate::hand_score::HandScore;
+    use crate::hand_stats::HandStats;
+    use crate::hand_value::HandCategory;
+
+    fn card_names_to_hand_score<const N: usize>(deck: &Deck, cards: &[&str; N]) -> HandScore {
+        // Check that the card names can be parsed, and that the test author
+        // gave a valid hand
+        let hand = deck.draw_hand(cards).unwrap();
+        check_unique(&hand.cards).unwrap();
+        HandScore::from(&hand)
+    }
+
+    /// Same as `card_names_to_hand_score`, but allows duplicate cards, as long
+    /// as the hand could be dealt from a shoe with `num_decks` decks.
+    fn card_names_to_shoe_hand_score<const N: usize>(
+        deck: &Deck,
+        cards: &[&str; N],
+        num_decks: usize,
+    ) -> HandScore {
+        let hand = deck.draw_hand(cards).unwrap();
+        assert!(cards_fit_in_shoe(&hand, num_decks));
+        HandScore::from(&hand)
+    }
 
     #[test]
     fn five_card_hand_scores() {
@@ -143,66 +264,50 @@ mod tests {
 
         assert_eq!(
             card_names_to_hand_score(&deck, &["5♣", "8♣", "8♠", "7♣", "9♦"]),
-            HandScore {
-                pair: 1,
-                high_card: 1,
-                ..Default::default()
-            }
+            HandScore::from_categories(&[HandCategory::HighCard, HandCategory::Pair])
         );
 
         assert_eq!(
             card_names_to_hand_score(&deck, &["5♣", "9♣", "8♣", "7♣", "2♣"]),
-            HandScore {
-                flush: 1,
-                high_card: 1,
-                ..Default::default()
-            }
+            HandScore::from_categories(&[HandCategory::HighCard, HandCategory::Flush])
         );
         assert_eq!(
             card_names_to_hand_score(&deck, &["5♣", "4♦", "7♣", "7♦", "4♥"]),
-            HandScore {
-                pair: 1,
-                two_pair: 1,
-                high_card: 1,
-                ..Default::default()
-            },
+            HandScore::from_categories(&[
+                HandCategory::HighCard,
+                HandCategory::Pair,
+                HandCategory::TwoPair
+            ]),
         );
         assert_eq!(
             card_names_to_hand_score(&deck, &["5♣", "4♦", "7♣", "5♦", "5♥"]),
-            HandScore {
-                pair: 1,
-                three_of_a_kind: 1,
-                high_card: 1,
-                ..Default::default()
-            },
+            HandScore::from_categories(&[
+                HandCategory::HighCard,
+                HandCategory::Pair,
+                HandCategory::ThreeOfAKind
+            ]),
         );
         assert_eq!(
             card_names_to_hand_score(&deck, &["5♦", "9♠", "7♠", "8♦", "6♥"]),
-            HandScore {
-                straight: 1,
-                high_card: 1,
-                ..Default::default()
-            }
+            HandScore::from_categories(&[HandCategory::HighCard, HandCategory::Straight])
         );
         assert_eq!(
             card_names_to_hand_score(&deck, &["4♦", "5♦", "5♣", "4♣", "5♥"]),
-            HandScore {
-                pair: 1,
-                three_of_a_kind: 1,
-                full_house: 1,
-                high_card: 1,
-                ..Default::default()
-            },
+            HandScore::from_categories(&[
+                HandCategory::HighCard,
+                HandCategory::Pair,
+                HandCategory::ThreeOfAKind,
+                HandCategory::FullHouse
+            ]),
         );
         assert_eq!(
             card_names_to_hand_score(&deck, &["9♥", "7♥", "8♥", "T♥", "J♥"]),
-            HandScore {
-                straight: 1,
-                flush: 1,
-                straight_flush: 1,
-                high_card: 1,
-                ..Default::default()
-            },
+            HandScore::from_categories(&[
+                HandCategory::HighCard,
+                HandCategory::Straight,
+                HandCategory::Flush,
+                HandCategory::StraightFlush
+            ]),
         );
     }
 
@@ -212,66 +317,179 @@ mod tests {
 
         assert_eq!(
             card_names_to_hand_score(&deck, &["5♣", "8♣", "3♣", "8♠", "7♣", "T♥", "9♦"]),
-            HandScore {
-                pair: 1,
-                high_card: 1,
-                ..Default::default()
-            }
+            HandScore::from_categories(&[HandCategory::HighCard, HandCategory::Pair])
         );
         assert_eq!(
             card_names_to_hand_score(&deck, &["5♣", "9♣", "8♣", "T♥", "9♦", "7♣", "2♣"]),
-            HandScore {
-                flush: 1,
-                pair: 1,
-                high_card: 1,
-                ..Default::default()
-            },
+            HandScore::from_categories(&[
+                HandCategory::HighCard,
+                HandCategory::Pair,
+                HandCategory::Flush
+            ]),
         );
         assert_eq!(
             card_names_to_hand_score(&deck, &["5♣", "4♦", "7♣", "9♣", "8♣", "7♦", "4♥"]),
-            HandScore {
-                pair: 1,
-                two_pair: 1,
-                high_card: 1,
-                ..Default::default()
-            },
+            HandScore::from_categories(&[
+                HandCategory::HighCard,
+                HandCategory::Pair,
+                HandCategory::TwoPair
+            ]),
         );
         assert_eq!(
             card_names_to_hand_score(&deck, &["5♣", "4♦", "7♣", "5♦", "9♣", "8♣", "5♥"]),
-            HandScore {
-                pair: 1,
-                three_of_a_kind: 1,
-                high_card: 1,
-                ..Default::default()
-            },
+            HandScore::from_categories(&[
+                HandCategory::HighCard,
+                HandCategory::Pair,
+                HandCategory::ThreeOfAKind
+            ]),
         );
         assert_eq!(
             card_names_to_hand_score(&deck, &["5♦", "9♠", "7♠", "8♦", "6♥", "T♦", "J♥"]),
-            HandScore {
-                straight: 1,
-                high_card: 1,
-                ..Default::default()
-            }
+            HandScore::from_categories(&[HandCategory::HighCard, HandCategory::Straight])
         );
         assert_eq!(
             card_names_to_hand_score(&deck, &["4♦", "5♦", "8♦", "6♥", "5♣", "4♣", "5♥"]),
-            HandScore {
-                pair: 1,
-                three_of_a_kind: 1,
-                full_house: 1,
-                high_card: 1,
-                ..Default::default()
-            },
+            HandScore::from_categories(&[
+                HandCategory::HighCard,
+                HandCategory::Pair,
+                HandCategory::ThreeOfAKind,
+                HandCategory::FullHouse
+            ]),
         );
         assert_eq!(
             card_names_to_hand_score(&deck, &["9♥", "7♥", "8♥", "6♥", "5♣", "T♥", "J♥"]),
-            HandScore {
-                straight: 1,
-                flush: 1,
-                straight_flush: 1,
-                high_card: 1,
-                ..Default::default()
-            },
+            HandScore::from_categories(&[
+                HandCategory::HighCard,
+                HandCategory::Straight,
+                HandCategory::Flush,
+                HandCategory::StraightFlush
+            ]),
+        );
+    }
+
+    /// Seven card hands where the best five cards are not the obvious ones.
+    #[test]
+    fn seven_card_hand_edge_cases() {
+        let deck = Deck::new();
+
+        // A gap below the straight, and an ace-high straight.
+        assert_eq!(
+            card_names_to_hand_score(&deck, &["2♦", "3♠", "6♣", "7♣", "8♥", "9♦", "T♥"]),
+            HandScore::from_categories(&[HandCategory::HighCard, HandCategory::Straight])
+        );
+        assert_eq!(
+            card_names_to_hand_score(&deck, &["T♦", "J♠", "Q♣", "K♣", "A♥", "2♦", "2♥"]),
+            HandScore::from_categories(&[
+                HandCategory::HighCard,
+                HandCategory::Pair,
+                HandCategory::Straight
+            ])
         );
+        // Two sets of trips make a full house.
+        assert_eq!(
+            card_names_to_hand_score(&deck, &["4♦", "4♠", "9♣", "4♣", "9♥", "9♦", "K♥"]),
+            HandScore::from_categories(&[
+                HandCategory::HighCard,
+                HandCategory::Pair,
+                HandCategory::ThreeOfAKind,
+                HandCategory::FullHouse
+            ])
+        );
+        // A straight and a flush, but no straight flush.
+        assert_eq!(
+            card_names_to_hand_score(&deck, &["5♥", "6♥", "7♥", "8♥", "9♣", "K♥", "2♠"]),
+            HandScore::from_categories(&[
+                HandCategory::HighCard,
+                HandCategory::Straight,
+                HandCategory::Flush
+            ])
+        );
+    }
+
+    /// The ace plays either low or high, but a straight can't wrap around
+    /// from the king to the deuce.
+    #[test]
+    fn straights_with_either_ace() {
+        let deck = Deck::new();
+        let straight =
+            HandScore::from_categories(&[HandCategory::HighCard, HandCategory::Straight]);
+        for names in [
+            ["A♣", "2♦", "3♣", "4♥", "5♠"],
+            ["T♣", "J♦", "Q♣", "K♥", "A♠"],
+        ] {
+            assert_eq!(
+                card_names_to_hand_score(&deck, &names),
+                straight,
+                "{names:?}"
+            );
+        }
+        assert_eq!(
+            card_names_to_hand_score(&deck, &["Q♣", "K♦", "A♣", "2♥", "3♠"]),
+            HandScore::from_categories(&[HandCategory::HighCard])
+        );
+
+        // 10 * 4^5 five card straights, including the 40 straight flushes.
+        let mut num_straights = 0;
+        for_each_hand_in_shoe::<5, _>(1, |hand, weight| {
+            if is_straight(&HandStats::from(hand)) {
+                num_straights += weight;
+            }
+        });
+        assert_eq!(num_straights, 10 * 1024);
+    }
+
+    #[test]
+    fn duplicate_card_hand_scores() {
+        let deck = Deck::new();
+
+        assert_eq!(
+            card_names_to_shoe_hand_score(&deck, &["9♥", "9♥", "8♥", "T♥", "J♥"], 2),
+            HandScore::from_categories(&[
+                HandCategory::HighCard,
+                HandCategory::Pair,
+                HandCategory::Flush
+            ]),
+        );
+        assert_eq!(
+            card_names_to_shoe_hand_score(&deck, &["Q♥", "Q♣", "Q♥", "Q♠", "Q♦"], 2),
+            HandScore::from_categories(&[
+                HandCategory::HighCard,
+                HandCategory::Pair,
+                HandCategory::ThreeOfAKind,
+                HandCategory::FourOfAKind,
+                HandCategory::FiveOfAKind
+            ]),
+        );
+        assert_eq!(
+            card_names_to_shoe_hand_score(&deck, &["3♦", "3♦", "3♦", "3♦", "3♦", "3♠", "K♥"], 5),
+            HandScore::from_categories(&[
+                HandCategory::HighCard,
+                HandCategory::Pair,
+                HandCategory::ThreeOfAKind,
+                HandCategory::Flush,
+                HandCategory::FourOfAKind,
+                HandCategory::FiveOfAKind,
+                HandCategory::FlushFive
+            ]),
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let deck = Deck::new();
+        let cards = ["A♥", "A♣", "7♦", "7♠", "2♥"];
+        let score = card_names_to_hand_score(&deck, &cards);
+        let json = serde_json::to_string(&score).unwrap();
+        assert!(json.starts_with(r#"{"high_card":1,"pair":1,"two_pair":1,"three_of_a_kind":0,"#));
+        assert_eq!(serde_json::from_str::<HandScore>(&json).unwrap(), score);
+        // Categories can be missing or in any order, but must be known.
+        let json = r#"{"two_pair":1,"high_card":1,"pair":1}"#;
+        assert_eq!(serde_json::from_str::<HandScore>(json).unwrap(), score);
+        assert!(serde_json::from_str::<HandScore>(r#"{"one_pair":1}"#).is_err());
+
+        let stats = HandStats::from(&deck.draw_hand(&cards).unwrap());
+        let json = serde_json::to_string(&stats).unwrap();
+        assert_eq!(serde_json::from_str::<HandStats>(&json).unwrap(), stats);
     }
 }
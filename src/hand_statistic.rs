@@ -0,0 +1,312 @@
+//! Statistics that are collected one hand at a time, such as the number of
+//! hands in each category, and the sampling and enumeration that feed them.
+//! Partial results for separate batches of hands are merged at the end, so
+//! the batches can run on different threads.
+
+use crate::card::Card;
+use crate::error::Result;
+use crate::hand::{check_known, for_each_hand_in_shoe_with_known, Hand};
+use crate::parallel::{batch_rng, parallel_batches};
+use rand::Rng;
+
+/// An accumulator for some per-hand metric of N card hands. Every function
+/// below starts each batch of hands from a copy of an empty statistic, so
+/// any settings it needs (such as a counting mode or a closure) are kept in
+/// the statistic itself.
+pub trait HandStatistic<const N: usize>: Clone + Send + Sync {
+    /// Adds one hand.
+    fn observe(&mut self, hand: &Hand<N>);
+
+    /// Adds a hand that can be dealt in `weight` different ways, as if it had
+    /// been observed `weight` times. Override this when the metric can add
+    /// the weight directly.
+    fn observe_weighted(&mut self, hand: &Hand<N>, weight: u64) {
+        for _ in 0..weight {
+            self.observe(hand);
+        }
+    }
+
+    /// Adds every hand that was observed by `other`.
+    fn merge(&mut self, other: &Self);
+}
+
+/// How many hands take each value of a metric, such as the number of
+/// different ranks or the most cards of one suit. The counts are indexed by
+/// the value of the metric.
+#[derive(Clone, Debug)]
+pub struct MetricCounts<F> {
+    metric: F,
+    counts: Vec<u64>,
+}
+
+impl<F> MetricCounts<F> {
+    pub fn new(metric: F) -> MetricCounts<F> {
+        MetricCounts {
+            metric,
+            counts: vec![],
+        }
+    }
+
+    /// Number of hands for each value, up to the largest value seen.
+    pub fn counts(&self) -> &[u64] {
+        &self.counts
+    }
+
+    pub fn num_hands(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// Share of the hands that took each value.
+    pub fn probabilities(&self) -> Vec<f64> {
+        let num_hands = self.num_hands() as f64;
+        self.counts
+            .iter()
+            .map(|count| *count as f64 / num_hands)
+            .collect()
+    }
+}
+
+impl<const N: usize, F> HandStatistic<N> for MetricCounts<F>
+where
+    F: Fn(&Hand<N>) -> usize + Clone + Send + Sync,
+{
+    fn observe(&mut self, hand: &Hand<N>) {
+        self.observe_weighted(hand, 1);
+    }
+
+    fn observe_weighted(&mut self, hand: &Hand<N>, weight: u64) {
+        let value = (self.metric)(hand);
+        if value >= self.counts.len() {
+            self.counts.resize(value + 1, 0);
+        }
+        self.counts[value] += weight;
+    }
+
+    fn merge(&mut self, other: &MetricCounts<F>) {
+        if other.counts.len() > self.counts.len() {
+            self.counts.resize(other.counts.len(), 0);
+        }
+        for (count, other_count) in self.counts.iter_mut().zip(other.counts.iter()) {
+            *count += other_count;
+        }
+    }
+}
+
+/// How many hands pass a test, such as holding at least one ace.
+#[derive(Clone, Debug)]
+pub struct PredicateCount<F> {
+    predicate: F,
+    pub num_matches: u64,
+    pub num_hands: u64,
+}
+
+impl<F> PredicateCount<F> {
+    pub fn new(predicate: F) -> PredicateCount<F> {
+        PredicateCount {
+            predicate,
+            num_matches: 0,
+            num_hands: 0,
+        }
+    }
+
+    pub fn probability(&self) -> f64 {
+        self.num_matches as f64 / self.num_hands as f64
+    }
+}
+
+impl<const N: usize, F> HandStatistic<N> for PredicateCount<F>
+where
+    F: Fn(&Hand<N>) -> bool + Clone + Send + Sync,
+{
+    fn observe(&mut self, hand: &Hand<N>) {
+        self.observe_weighted(hand, 1);
+    }
+
+    fn observe_weighted(&mut self, hand: &Hand<N>, weight: u64) {
+        if (self.predicate)(hand) {
+            self.num_matches += weight;
+        }
+        self.num_hands += weight;
+    }
+
+    fn merge(&mut self, other: &PredicateCount<F>) {
+        self.num_matches += other.num_matches;
+        self.num_hands += other.num_hands;
+    }
+}
+
+/// Adds `num_samples` hands drawn from a shoe of `num_decks` decks to a copy
+/// of `empty`. Every hand starts with the `known` cards, and the rest of it
+/// is drawn from the shoe without them. Fails if there are no decks, or the
+/// known cards don't fit in the shoe or the hand.
+pub fn sample_statistic<const N: usize, S: HandStatistic<N>, R: Rng>(
+    rng: &mut R,
+    empty: &S,
+    known: &[Card],
+    num_samples: u32,
+    num_decks: usize,
+) -> Result<S> {
+    check_known::<N>(known, num_decks)?;
+    let mut statistic = empty.clone();
+    for _ in 0..num_samples {
+        statistic.observe(&Hand::<N>::draw_unchecked(rng, known, num_decks));
+    }
+    Ok(statistic)
+}
+
+/// Multi-threaded version of `sample_statistic`, which samples one batch of
+/// hands per thread and merges the results. Each batch draws from its own
+/// generator derived from `seed`, so the same seed and number of threads
+/// always give the same result.
+pub fn parallel_sample_statistic<const N: usize, S: HandStatistic<N>>(
+    empty: &S,
+    known: &[Card],
+    num_samples: u32,
+    num_threads: u32,
+    num_decks: usize,
+    seed: u64,
+) -> Result<S> {
+    check_known::<N>(known, num_decks)?;
+    parallel_batches(
+        num_samples,
+        num_threads,
+        empty.clone(),
+        |batch, batch_size| {
+            let rng = &mut batch_rng(seed, batch);
+            sample_statistic(rng, empty, known, batch_size, num_decks)
+        },
+        S::merge,
+    )
+}
+
+/// Exact version of `sample_statistic`: observes every hand that can be dealt
+/// from a shoe of `num_decks` decks, weighted by how many ways it can be
+/// dealt. Practical for five card hands, but slow for seven unless some of
+/// the cards are known.
+pub fn enumerate_statistic<const N: usize, S: HandStatistic<N>>(
+    empty: &S,
+    known: &[Card],
+    num_decks: usize,
+) -> Result<S> {
+    check_known::<N>(known, num_decks)?;
+    let mut statistic = empty.clone();
+    for_each_hand_in_shoe_with_known::<N, _>(known, num_decks, |hand, weight| {
+        statistic.observe_weighted(hand, weight);
+    });
+    Ok(statistic)
+}
+
+//////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use crate::card::{AceOrder, Rank};
+    use crate::deck::Deck;
+    use crate::error::PokerError;
+    use crate::hand::Hand;
+    use crate::hand_statistic::enumerate_statistic;
+    use crate::hand_statistic::parallel_sample_statistic;
+    use crate::hand_statistic::sample_statistic;
+    use crate::hand_statistic::MetricCounts;
+    use crate::hand_statistic::PredicateCount;
+    use crate::hand_stats::HandStats;
+    use rand::SeedableRng;
+
+    fn max_suit_count(hand: &Hand<5>) -> usize {
+        *HandStats::from(hand).suit_count.iter().max().unwrap()
+    }
+
+    fn num_ranks(hand: &Hand<5>) -> usize {
+        HandStats::from(hand)
+            .rank_count
+            .iter()
+            .filter(|count| **count > 0)
+            .count()
+    }
+
+    /// Exact counts of the most cards of one suit, and of the number of
+    /// different ranks, in all C(52, 5) hands.
+    #[test]
+    fn enumerate_metrics() {
+        let suits = enumerate_statistic(&MetricCounts::new(max_suit_count), &[], 1).unwrap();
+        assert_eq!(suits.num_hands(), 2598960);
+        // Four flushes: one of four suits, four of its cards, and one of the
+        // 39 cards of other suits.
+        assert_eq!(suits.counts()[4], 4 * 715 * 39);
+        assert_eq!(suits.counts()[5], 4 * 1287);
+
+        let ranks = enumerate_statistic(&MetricCounts::new(num_ranks), &[], 1).unwrap();
+        // Five different ranks, in any suits: high card, straights and flushes.
+        assert_eq!(ranks.counts()[5], 1287 * 1024);
+        assert_eq!(ranks.counts()[1], 0);
+    }
+
+    #[test]
+    fn sampled_predicate() {
+        let has_ace = |hand: &Hand<5>| hand.cards.iter().any(|card| card.rank() == Rank::Ace);
+        let mut rng = rand::rngs::StdRng::seed_from_u64(2718);
+        let sampled =
+            sample_statistic(&mut rng, &PredicateCount::new(has_ace), &[], 20000, 1).unwrap();
+        let exact = enumerate_statistic(&PredicateCount::new(has_ace), &[], 1).unwrap();
+        // One minus C(48, 5) / C(52, 5)
+        assert_eq!(exact.num_matches, 2598960 - 1712304);
+        assert_eq!(sampled.num_hands, 20000);
+        assert!((sampled.probability() - exact.probability()).abs() < 0.01);
+        assert!(sample_statistic(&mut rng, &PredicateCount::new(has_ace), &[], 10, 0).is_err());
+    }
+
+    /// Known cards are in every hand, and can't be dealt again.
+    #[test]
+    fn known_cards() {
+        let ace = Deck::new().draw_cards(&["A♠"]).unwrap()[0];
+        let second_ace =
+            |hand: &Hand<5>| hand.cards.iter().filter(|card| **card == ace).count() > 1;
+        let exact = enumerate_statistic(&PredicateCount::new(second_ace), &[ace], 1).unwrap();
+        assert_eq!(exact.num_hands, 249900);
+        assert_eq!(exact.num_matches, 0);
+        // Two decks have another ace of spades, which comes with C(102, 3)
+        // ways to deal the other three cards.
+        let exact = enumerate_statistic(&PredicateCount::new(second_ace), &[ace], 2).unwrap();
+        assert_eq!(exact.num_matches, 171700);
+        let sampled =
+            parallel_sample_statistic(&PredicateCount::new(second_ace), &[ace], 1001, 4, 2, 3)
+                .unwrap();
+        assert_eq!(sampled.num_hands, 1001);
+        assert!(sampled.num_matches > 0);
+
+        let empty = PredicateCount::new(second_ace);
+        assert!(matches!(
+            enumerate_statistic(&empty, &[ace, ace], 1),
+            Err(PokerError::DuplicateCards)
+        ));
+        assert!(enumerate_statistic(&empty, &[ace, ace], 2).is_ok());
+        assert!(matches!(
+            enumerate_statistic(&empty, &[ace; 6], 6),
+            Err(PokerError::InvalidCardCount { found: 6, .. })
+        ));
+    }
+
+    #[test]
+    fn parallel_merges_batches() {
+        let highest = |hand: &Hand<5>| {
+            let card = hand.cards.iter().max().unwrap();
+            card.rank().strength(AceOrder::High)
+        };
+        let counts =
+            parallel_sample_statistic(&MetricCounts::new(highest), &[], 1001, 4, 1, 7).unwrap();
+        assert_eq!(counts.num_hands(), 1001);
+        // The same seed samples the same hands, and another seed doesn't.
+        let again =
+            parallel_sample_statistic(&MetricCounts::new(highest), &[], 1001, 4, 1, 7).unwrap();
+        assert_eq!(again.counts(), counts.counts());
+        let other =
+            parallel_sample_statistic(&MetricCounts::new(highest), &[], 1001, 4, 1, 8).unwrap();
+        assert_ne!(other.counts(), counts.counts());
+        // There are only four deuces in a deck, and about a third of all
+        // hands have an ace.
+        assert_eq!(counts.counts()[0], 0);
+        let aces = counts.counts()[Rank::Ace.strength(AceOrder::High)];
+        assert!(counts.counts().iter().all(|count| *count <= aces));
+        assert!(parallel_sample_statistic(&MetricCounts::new(highest), &[], 10, 4, 0, 7).is_err());
+    }
+}
@@ -6,16 +6,22 @@ use std::convert::From;
 
 use std::fmt;
 
-#[derive(Default, PartialEq, Debug)]
+#[derive(Default, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HandStats {
     pub rank_count: [usize; Rank::NUM_RANKS],
     pub suit_count: [usize; Suit::NUM_SUITS],
+    /// Number of copies of each card, indexed by `[rank][suit]`. Only a hand
+    /// dealt from a multi-deck shoe can have a count larger than one.
+    pub card_count: [[usize; Suit::NUM_SUITS]; Rank::NUM_RANKS],
 }
 
 impl HandStats {
     pub fn insert(&mut self, card: Card) {
-        self.rank_count[card.rank().id] += 1;
-        self.suit_count[card.suit().id] += 1;
+        let (rank, suit) = (card.rank().index(), card.suit().index());
+        self.rank_count[rank] += 1;
+        self.suit_count[suit] += 1;
+        self.card_count[rank][suit] += 1;
     }
 
     pub fn insert_hand<const N: usize>(&mut self, hand: &Hand<N>) {
@@ -24,6 +30,15 @@ impl HandStats {
         }
     }
 
+    /// Stats for any number of cards.
+    pub fn from_cards(cards: &[Card]) -> HandStats {
+        let mut hand_stats: HandStats = Default::default();
+        for card in cards {
+            hand_stats.insert(*card);
+        }
+        hand_stats
+    }
+
     pub fn count_cards(&self) -> usize {
         let mut count = 0;
         for suit_count in self.suit_count {
@@ -31,10 +46,19 @@ impl HandStats {
         }
         count
     }
+
+    /// Largest number of copies of any single card in the hand.
+    pub fn max_duplicate_count(&self) -> usize {
+        self.card_count
+            .iter()
+            .flat_map(|suits| suits.iter())
+            .copied()
+            .max()
+            .unwrap_or(0)
+    }
 }
 
 impl<const N: usize> From<&Hand<N>> for HandStats {
-    #[allow(dead_code)]
     fn from(hand: &Hand<N>) -> HandStats {
         let mut hand_stats: HandStats = Default::default();
         hand_stats.insert_hand(hand);
@@ -48,7 +72,7 @@ fn hand_stats_array_string<const N: usize, T>(counts: [usize; N], func: T) -> St
 where
     T: Fn(usize) -> String,
 {
-    let mut suits = format!("");
+    let mut suits = String::new();
     for (suit_id, count) in counts.iter().enumerate() {
         if count > &0 {
             let line = format!("[{}]: {}, ", func(suit_id), count);
@@ -61,8 +85,8 @@ where
 
 impl fmt::Display for HandStats {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let suits = hand_stats_array_string(self.suit_count, |id| Suit { id }.to_string());
-        let ranks = hand_stats_array_string(self.rank_count, |id| Rank { id }.to_string());
+        let suits = hand_stats_array_string(self.suit_count, |index| Suit::ALL[index].to_string());
+        let ranks = hand_stats_array_string(self.rank_count, |index| Rank::ALL[index].to_string());
         write!(
             f,
             "HandStats:\n  Count: {}\n  Suits: {suits}\n  Ranks: {ranks}",
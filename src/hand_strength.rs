@@ -0,0 +1,243 @@
+use crate::card::Card;
+use crate::deck::remaining_cards;
+use crate::error::{PokerError, Result};
+use crate::hand_value::HandValue;
+use crate::holdem::{validate_street_hand, BOARD_SIZE, HOLE_CARDS};
+use crate::parallel::prelude::*;
+use itertools::Itertools;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::cmp::Ordering;
+use std::fmt;
+
+const AHEAD: usize = 0;
+const TIED: usize = 1;
+const BEHIND: usize = 2;
+
+fn standing(hero: HandValue, opponent: HandValue) -> usize {
+    match hero.cmp(&opponent) {
+        Ordering::Greater => AHEAD,
+        Ordering::Equal => TIED,
+        Ordering::Less => BEHIND,
+    }
+}
+
+/// Counts for the hand strength and potential of hold'em hole cards against a
+/// single random opponent, as described by Billings et al. in "Opponent
+/// Modeling in Poker". Each count is indexed by whether the hero is ahead,
+/// tied or behind.
+#[derive(Default, PartialEq, Debug, Clone)]
+pub struct HandPotential {
+    /// Opponent holdings by how the hero stands with the current board.
+    pub now: [u64; 3],
+    /// Opponent holdings and completed boards, by how the hero stands now
+    /// and then on the river.
+    pub river: [[u64; 3]; 3],
+}
+
+impl HandPotential {
+    pub fn merge(&mut self, other: &HandPotential) {
+        for now in 0..3 {
+            self.now[now] += other.now[now];
+            for river in 0..3 {
+                self.river[now][river] += other.river[now][river];
+            }
+        }
+    }
+
+    /// Share of opponent holdings beaten now, counting ties as half (HS).
+    pub fn hand_strength(&self) -> f64 {
+        let total: u64 = self.now.iter().sum();
+        (self.now[AHEAD] as f64 + self.now[TIED] as f64 / 2.0) / total as f64
+    }
+
+    fn river_totals(&self) -> [f64; 3] {
+        self.river.map(|counts| counts.iter().sum::<u64>() as f64)
+    }
+
+    /// Chance that a hand behind or tied now is ahead on the river (PPot).
+    /// Ties count as half on both streets. Zero if the hand is always ahead.
+    pub fn positive_potential(&self) -> f64 {
+        let totals = self.river_totals();
+        let gains = self.river[BEHIND][AHEAD] as f64
+            + self.river[BEHIND][TIED] as f64 / 2.0
+            + self.river[TIED][AHEAD] as f64 / 2.0;
+        let chances = totals[BEHIND] + totals[TIED] / 2.0;
+        if chances > 0.0 {
+            gains / chances
+        } else {
+            0.0
+        }
+    }
+
+    /// Chance that a hand ahead or tied now is behind on the river (NPot).
+    /// Ties count as half on both streets. Zero if the hand is always behind.
+    pub fn negative_potential(&self) -> f64 {
+        let totals = self.river_totals();
+        let losses = self.river[AHEAD][BEHIND] as f64
+            + self.river[AHEAD][TIED] as f64 / 2.0
+            + self.river[TIED][BEHIND] as f64 / 2.0;
+        let chances = totals[AHEAD] + totals[TIED] / 2.0;
+        if chances > 0.0 {
+            losses / chances
+        } else {
+            0.0
+        }
+    }
+
+    /// Effective hand strength: HS(1 - NPot) + (1 - HS)PPot, the chance of
+    /// being ahead on the river. The optimistic form that ignores the
+    /// negative potential is `hand_strength() + (1 - hand_strength()) *
+    /// positive_potential()`.
+    pub fn effective_hand_strength(&self) -> f64 {
+        let hand_strength = self.hand_strength();
+        hand_strength * (1.0 - self.negative_potential())
+            + (1.0 - hand_strength) * self.positive_potential()
+    }
+}
+
+impl fmt::Display for HandPotential {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "HS:   {:>7.3}%\nPPot: {:>7.3}%\nNPot: {:>7.3}%\nEHS:  {:>7.3}%",
+            100.0 * self.hand_strength(),
+            100.0 * self.positive_potential(),
+            100.0 * self.negative_potential(),
+            100.0 * self.effective_hand_strength()
+        )
+    }
+}
+
+fn value_with_board(hole_cards: &[Card], board: &[Card], dealt: &[Card]) -> HandValue {
+    let cards: Vec<Card> = hole_cards
+        .iter()
+        .chain(board.iter())
+        .chain(dealt.iter())
+        .copied()
+        .collect();
+    HandValue::from_cards(&cards)
+}
+
+/// Exact hand strength and potential of the hole cards on the flop, turn or
+/// river. Every opponent holding is checked against every way to complete the
+/// board, with the opponent holdings split across threads. Fails if the cards
+/// are not valid or there are fewer than three board cards, where sampling is
+/// needed to finish in a reasonable time.
+pub fn hand_potential(hole_cards: &[Card], board: &[Card]) -> Result<HandPotential> {
+    validate_street_hand(hole_cards, board)?;
+    if board.len() < 3 {
+        return Err(PokerError::InvalidCardCount {
+            what: "the board",
+            expected: "3 to 5",
+            found: board.len(),
+        });
+    }
+    let known: Vec<Card> = hole_cards.iter().chain(board.iter()).copied().collect();
+    let hero_now = value_with_board(hole_cards, board, &[]);
+    let stub = remaining_cards(&known);
+    let opponents: Vec<Vec<Card>> = stub.iter().copied().combinations(HOLE_CARDS).collect();
+    let counts: Vec<HandPotential> = opponents
+        .into_par_iter()
+        .map(|opponent| {
+            let mut potential = HandPotential::default();
+            let now = standing(hero_now, value_with_board(&opponent, board, &[]));
+            potential.now[now] += 1;
+            let rest: Vec<Card> = stub
+                .iter()
+                .copied()
+                .filter(|card| !opponent.contains(card))
+                .collect();
+            for dealt in rest.into_iter().combinations(BOARD_SIZE - board.len()) {
+                let river = standing(
+                    value_with_board(hole_cards, board, &dealt),
+                    value_with_board(&opponent, board, &dealt),
+                );
+                potential.river[now][river] += 1;
+            }
+            potential
+        })
+        .collect();
+    let mut potential = HandPotential::default();
+    for count in &counts {
+        potential.merge(count);
+    }
+    Ok(potential)
+}
+
+/// Sampled version of `hand_potential`, which also works before the flop.
+/// Each sample deals a random opponent holding and the rest of the board.
+pub fn sample_hand_potential<R: Rng>(
+    rng: &mut R,
+    hole_cards: &[Card],
+    board: &[Card],
+    num_samples: u32,
+) -> Result<HandPotential> {
+    validate_street_hand(hole_cards, board)?;
+    let known: Vec<Card> = hole_cards.iter().chain(board.iter()).copied().collect();
+    let hero_now = value_with_board(hole_cards, board, &[]);
+    let mut stub = remaining_cards(&known);
+    let mut potential = HandPotential::default();
+    for _ in 0..num_samples {
+        let (dealt, _) = stub.partial_shuffle(rng, HOLE_CARDS + BOARD_SIZE - board.len());
+        let (opponent, dealt) = dealt.split_at(HOLE_CARDS);
+        let now = standing(hero_now, value_with_board(opponent, board, &[]));
+        let river = standing(
+            value_with_board(hole_cards, board, dealt),
+            value_with_board(opponent, board, dealt),
+        );
+        potential.now[now] += 1;
+        potential.river[now][river] += 1;
+    }
+    Ok(potential)
+}
+
+//////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use crate::deck::Deck;
+    use crate::hand_strength::hand_potential;
+    use crate::hand_strength::sample_hand_potential;
+    use rand::SeedableRng;
+
+    /// The example from "Opponent Modeling in Poker": A♦Q♣ on a 3♥4♣J♥ flop.
+    #[test]
+    fn flop_potential() {
+        let deck = Deck::new();
+        let hole_cards = deck.draw_cards(&["A♦", "Q♣"]).unwrap();
+        let board = deck.draw_cards(&["3♥", "4♣", "J♥"]).unwrap();
+        let potential = hand_potential(&hole_cards, &board).unwrap();
+        assert_eq!(potential.now.iter().sum::<u64>(), 1081);
+        assert_eq!(potential.river.iter().flatten().sum::<u64>(), 1081 * 990);
+        assert!((potential.hand_strength() - 0.585).abs() < 0.001);
+        assert!((potential.positive_potential() - 0.208).abs() < 0.001);
+        assert!((potential.negative_potential() - 0.274).abs() < 0.001);
+        assert_eq!(
+            potential.to_string(),
+            "HS:    58.511%\nPPot:  20.832%\nNPot:  27.369%\nEHS:   51.140%"
+        );
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(39);
+        let sampled = sample_hand_potential(&mut rng, &hole_cards, &board, 50000).unwrap();
+        assert!((sampled.hand_strength() - potential.hand_strength()).abs() < 0.01);
+        assert!(
+            (sampled.effective_hand_strength() - potential.effective_hand_strength()).abs() < 0.01
+        );
+    }
+
+    /// On the river there is nothing left to come, and the nuts always win.
+    #[test]
+    fn river_potential() {
+        let deck = Deck::new();
+        let hole_cards = deck.draw_cards(&["7♦", "7♠"]).unwrap();
+        let board = deck.draw_cards(&["K♥", "Q♥", "7♥", "7♣", "2♦"]).unwrap();
+        let potential = hand_potential(&hole_cards, &board).unwrap();
+        assert_eq!(potential.hand_strength(), 1.0);
+        assert_eq!(potential.positive_potential(), 0.0);
+        assert_eq!(potential.negative_potential(), 0.0);
+        assert_eq!(potential.effective_hand_strength(), 1.0);
+        assert!(hand_potential(&hole_cards, &[]).is_err());
+        assert!(hand_potential(&hole_cards, &board[..4]).is_ok());
+    }
+}
@@ -0,0 +1,583 @@
+use crate::card::AceOrder;
+use crate::card::Card;
+use crate::card::Rank;
+use crate::card::Suit;
+use crate::hand::Hand;
+use itertools::Itertools;
+use std::convert::From;
+use std::fmt;
+
+/// The scoring categories of a hand, from weakest to strongest. The last two
+/// can only occur when dealing from a shoe with more than one deck.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Copy, Clone, Hash)]
+pub enum HandCategory {
+    HighCard,
+    Pair,
+    TwoPair,
+    ThreeOfAKind,
+    Straight,
+    Flush,
+    FullHouse,
+    FourOfAKind,
+    StraightFlush,
+    FiveOfAKind,
+    FlushFive,
+}
+
+impl HandCategory {
+    pub const NUM_CATEGORIES: usize = 11;
+
+    /// Every category, from weakest to strongest.
+    pub const ALL: [HandCategory; HandCategory::NUM_CATEGORIES] = [
+        HandCategory::HighCard,
+        HandCategory::Pair,
+        HandCategory::TwoPair,
+        HandCategory::ThreeOfAKind,
+        HandCategory::Straight,
+        HandCategory::Flush,
+        HandCategory::FullHouse,
+        HandCategory::FourOfAKind,
+        HandCategory::StraightFlush,
+        HandCategory::FiveOfAKind,
+        HandCategory::FlushFive,
+    ];
+
+    /// Position in `ALL`, from zero for a high card.
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+
+    fn from_index(index: u32) -> HandCategory {
+        HandCategory::ALL[(index as usize).min(HandCategory::NUM_CATEGORIES - 1)]
+    }
+
+    /// One of: {high_card, pair, two_pair, ... five_of_a_kind, flush_five}
+    pub fn snake_case_name(&self) -> &'static str {
+        match self {
+            HandCategory::HighCard => "high_card",
+            HandCategory::Pair => "pair",
+            HandCategory::TwoPair => "two_pair",
+            HandCategory::ThreeOfAKind => "three_of_a_kind",
+            HandCategory::Straight => "straight",
+            HandCategory::Flush => "flush",
+            HandCategory::FullHouse => "full_house",
+            HandCategory::FourOfAKind => "four_of_a_kind",
+            HandCategory::StraightFlush => "straight_flush",
+            HandCategory::FiveOfAKind => "five_of_a_kind",
+            HandCategory::FlushFive => "flush_five",
+        }
+    }
+
+    /// One of: {High card, Pair, Two pair, ... Five of a kind, Flush five}
+    pub fn name(&self) -> &'static str {
+        match self {
+            HandCategory::HighCard => "High card",
+            HandCategory::Pair => "Pair",
+            HandCategory::TwoPair => "Two pair",
+            HandCategory::ThreeOfAKind => "Three of a kind",
+            HandCategory::Straight => "Straight",
+            HandCategory::Flush => "Flush",
+            HandCategory::FullHouse => "Full house",
+            HandCategory::FourOfAKind => "Four of a kind",
+            HandCategory::StraightFlush => "Straight flush",
+            HandCategory::FiveOfAKind => "Five of a kind",
+            HandCategory::FlushFive => "Flush five",
+        }
+    }
+
+    /// Number of cards in a five card hand that make the category, rather
+    /// than being kickers. A high card hand is made by its top card.
+    fn num_made_cards(&self) -> usize {
+        match self {
+            HandCategory::HighCard => 1,
+            HandCategory::Pair => 2,
+            HandCategory::ThreeOfAKind => 3,
+            HandCategory::TwoPair | HandCategory::FourOfAKind => 4,
+            HandCategory::Straight
+            | HandCategory::Flush
+            | HandCategory::FullHouse
+            | HandCategory::StraightFlush
+            | HandCategory::FiveOfAKind
+            | HandCategory::FlushFive => 5,
+        }
+    }
+}
+
+/// Comparable value of the best five card poker hand that can be made from a
+/// set of cards. Larger values are better hands, and hands that tie in a
+/// showdown have equal values.
+///
+/// The value is packed into a single integer: the category in the high bits,
+/// followed by the five ranks that decide ties (most significant first), four
+/// bits each. Ranks are "ace high" here: 0 is a two and 12 is an ace, and the
+/// ace only plays low in the five-high straight.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Copy, Clone, Hash)]
+pub struct HandValue {
+    value: u32,
+}
+
+const RANK_BITS: u32 = 4;
+const NUM_TIEBREAK_RANKS: usize = 5;
+const ACE_HIGH_ACE: usize = Rank::NUM_RANKS - 1;
+
+/// Converts from the card rank (ace is zero) to an ace-high rank index.
+pub(crate) fn ace_high_index(rank: Rank) -> usize {
+    rank.strength(AceOrder::High)
+}
+
+/// Converts from an ace-high rank index back to the card rank.
+fn rank_from_ace_high_index(index: usize) -> Rank {
+    Rank::ALL[(index + 1) % Rank::NUM_RANKS]
+}
+
+/// Returns the ace-high index of the top card of the best straight in the
+/// set of ranks (bit `i` set for ace-high index `i`), if there is one.
+fn best_straight(rank_mask: u32) -> Option<usize> {
+    for high in (4..Rank::NUM_RANKS).rev() {
+        let run = 0b11111 << (high - 4);
+        if rank_mask & run == run {
+            return Some(high);
+        }
+    }
+    // The wheel: A-2-3-4-5, where the ace plays low.
+    let wheel = (1 << ACE_HIGH_ACE) | 0b1111;
+    if rank_mask & wheel == wheel {
+        return Some(3);
+    }
+    None
+}
+
+/// Appends the ranks of the cards described by `rank_count` to `ranks`, from
+/// highest to lowest with one entry per card, skipping the `excluded` ranks,
+/// until `ranks` holds five entries.
+fn fill_kickers(rank_count: &[usize; Rank::NUM_RANKS], excluded: &[usize], ranks: &mut Vec<usize>) {
+    for rank in (0..Rank::NUM_RANKS).rev() {
+        if excluded.contains(&rank) {
+            continue;
+        }
+        for _ in 0..rank_count[rank] {
+            if ranks.len() == NUM_TIEBREAK_RANKS {
+                return;
+            }
+            ranks.push(rank);
+        }
+    }
+}
+
+/// Highest rank (ace-high index) with at least `count` cards, ignoring `excluded`.
+fn highest_with_count(
+    rank_count: &[usize; Rank::NUM_RANKS],
+    count: usize,
+    excluded: &[usize],
+) -> Option<usize> {
+    (0..Rank::NUM_RANKS)
+        .rev()
+        .find(|rank| rank_count[*rank] >= count && !excluded.contains(rank))
+}
+
+impl HandValue {
+    fn new(category: HandCategory, ranks: &[usize]) -> HandValue {
+        let mut value = category as u32;
+        for i in 0..NUM_TIEBREAK_RANKS {
+            value = (value << RANK_BITS) | ranks.get(i).map_or(0, |r| *r as u32);
+        }
+        HandValue { value }
+    }
+
+    /// Evaluates the best five card hand that can be made from `cards`. Works
+    /// for any number of cards, including duplicates from a multi-deck shoe.
+    /// Hands with fewer than five cards can only make the categories that fit.
+    pub fn from_cards(cards: &[Card]) -> HandValue {
+        let mut rank_count = [0; Rank::NUM_RANKS];
+        let mut suit_rank_count = [[0; Rank::NUM_RANKS]; Suit::NUM_SUITS];
+        for card in cards {
+            let rank = ace_high_index(card.rank());
+            rank_count[rank] += 1;
+            suit_rank_count[card.suit().index()][rank] += 1;
+        }
+        let rank_mask = |counts: &[usize; Rank::NUM_RANKS]| {
+            (0..Rank::NUM_RANKS)
+                .filter(|rank| counts[*rank] > 0)
+                .fold(0, |mask, rank| mask | (1 << rank))
+        };
+        let flush_suits = || {
+            suit_rank_count
+                .iter()
+                .filter(|counts| counts.iter().sum::<usize>() >= 5)
+        };
+
+        // Flush five: five copies of a single card.
+        if let Some(rank) = suit_rank_count
+            .iter()
+            .filter_map(|counts| highest_with_count(counts, 5, &[]))
+            .max()
+        {
+            return HandValue::new(HandCategory::FlushFive, &[rank]);
+        }
+        if let Some(rank) = highest_with_count(&rank_count, 5, &[]) {
+            return HandValue::new(HandCategory::FiveOfAKind, &[rank]);
+        }
+        if let Some(high) = flush_suits()
+            .filter_map(|counts| best_straight(rank_mask(counts)))
+            .max()
+        {
+            return HandValue::new(HandCategory::StraightFlush, &[high]);
+        }
+        if let Some(quads) = highest_with_count(&rank_count, 4, &[]) {
+            let mut ranks = vec![quads];
+            fill_kickers(&rank_count, &[quads], &mut ranks);
+            ranks.truncate(2);
+            return HandValue::new(HandCategory::FourOfAKind, &ranks);
+        }
+        if let Some(trips) = highest_with_count(&rank_count, 3, &[]) {
+            if let Some(pair) = highest_with_count(&rank_count, 2, &[trips]) {
+                return HandValue::new(HandCategory::FullHouse, &[trips, pair]);
+            }
+        }
+        if let Some(flush) = flush_suits()
+            .map(|counts| {
+                let mut ranks = Vec::with_capacity(NUM_TIEBREAK_RANKS);
+                fill_kickers(counts, &[], &mut ranks);
+                HandValue::new(HandCategory::Flush, &ranks)
+            })
+            .max()
+        {
+            return flush;
+        }
+        if let Some(high) = best_straight(rank_mask(&rank_count)) {
+            return HandValue::new(HandCategory::Straight, &[high]);
+        }
+        if let Some(trips) = highest_with_count(&rank_count, 3, &[]) {
+            let mut ranks = vec![trips];
+            fill_kickers(&rank_count, &[trips], &mut ranks);
+            ranks.truncate(3);
+            return HandValue::new(HandCategory::ThreeOfAKind, &ranks);
+        }
+        if let Some(high_pair) = highest_with_count(&rank_count, 2, &[]) {
+            if let Some(low_pair) = highest_with_count(&rank_count, 2, &[high_pair]) {
+                let mut ranks = vec![high_pair, low_pair];
+                fill_kickers(&rank_count, &[high_pair, low_pair], &mut ranks);
+                ranks.truncate(3);
+                return HandValue::new(HandCategory::TwoPair, &ranks);
+            }
+            let mut ranks = vec![high_pair];
+            fill_kickers(&rank_count, &[high_pair], &mut ranks);
+            ranks.truncate(4);
+            return HandValue::new(HandCategory::Pair, &ranks);
+        }
+        let mut ranks = Vec::with_capacity(NUM_TIEBREAK_RANKS);
+        fill_kickers(&rank_count, &[], &mut ranks);
+        HandValue::new(HandCategory::HighCard, &ranks)
+    }
+
+    pub fn category(&self) -> HandCategory {
+        HandCategory::from_index(self.value >> (RANK_BITS * NUM_TIEBREAK_RANKS as u32))
+    }
+
+    /// The ranks that decide ties within the category, most significant
+    /// first. For example, a full house returns the rank of the three of a
+    /// kind followed by the rank of the pair, and a straight returns its top
+    /// card. Ranks that are not used by the category are omitted.
+    pub fn tiebreak_ranks(&self) -> Vec<Rank> {
+        let num_ranks = match self.category() {
+            HandCategory::HighCard | HandCategory::Flush => 5,
+            HandCategory::Pair => 4,
+            HandCategory::TwoPair | HandCategory::ThreeOfAKind => 3,
+            HandCategory::FullHouse | HandCategory::FourOfAKind => 2,
+            HandCategory::Straight
+            | HandCategory::StraightFlush
+            | HandCategory::FiveOfAKind
+            | HandCategory::FlushFive => 1,
+        };
+        (0..num_ranks)
+            .map(|i| {
+                let shift = RANK_BITS * (NUM_TIEBREAK_RANKS - 1 - i) as u32;
+                rank_from_ace_high_index(((self.value >> shift) & 0b1111) as usize)
+            })
+            .collect()
+    }
+}
+
+impl<const N: usize> From<&Hand<N>> for HandValue {
+    fn from(hand: &Hand<N>) -> HandValue {
+        HandValue::from_cards(&hand.cards)
+    }
+}
+
+/// Describes the hand by its category and the ranks that matter, for example
+/// "Full house, Kings full of Sevens" or "Flush, Ace-high".
+impl fmt::Display for HandValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let category = self.category();
+        let ranks = self.tiebreak_ranks();
+        match category {
+            HandCategory::HighCard => write!(f, "{}, {}", category.name(), ranks[0].name()),
+            HandCategory::Pair
+            | HandCategory::ThreeOfAKind
+            | HandCategory::FourOfAKind
+            | HandCategory::FiveOfAKind
+            | HandCategory::FlushFive => {
+                write!(f, "{}, {}", category.name(), ranks[0].plural_name())
+            }
+            HandCategory::TwoPair => write!(
+                f,
+                "{}, {} and {}",
+                category.name(),
+                ranks[0].plural_name(),
+                ranks[1].plural_name()
+            ),
+            HandCategory::FullHouse => write!(
+                f,
+                "{}, {} full of {}",
+                category.name(),
+                ranks[0].plural_name(),
+                ranks[1].plural_name()
+            ),
+            HandCategory::StraightFlush if ranks[0] == Rank::Ace => write!(f, "Royal flush"),
+            HandCategory::Straight | HandCategory::Flush | HandCategory::StraightFlush => {
+                write!(f, "{}, {}-high", category.name(), ranks[0].name())
+            }
+        }
+    }
+}
+
+/// The five cards that make the best poker hand out of a larger set.
+#[derive(PartialEq, Eq, Debug, Copy, Clone, Hash)]
+pub struct BestFive {
+    pub value: HandValue,
+    /// The cards that make the category, then the kickers, each from the
+    /// highest rank down. Pairs and sets come before single cards, and the
+    /// ace comes last in a five-high straight.
+    pub cards: [Card; NUM_TIEBREAK_RANKS],
+}
+
+impl BestFive {
+    /// Picks the best five of `cards` by checking every combination. When
+    /// several combinations make the same hand, which only differ by suits
+    /// that don't matter, the first one in the order of `cards` is used.
+    /// Returns `None` if there are fewer than five cards.
+    pub fn from_cards(cards: &[Card]) -> Option<BestFive> {
+        let mut best: Option<BestFive> = None;
+        for five in cards.iter().copied().combinations(NUM_TIEBREAK_RANKS) {
+            let value = HandValue::from_cards(&five);
+            if best.is_none_or(|best| value > best.value) {
+                best = Some(BestFive {
+                    value,
+                    cards: array_init::array_init(|i| five[i]),
+                });
+            }
+        }
+        let mut best = best?;
+        let mut rank_count = [0; Rank::NUM_RANKS];
+        for card in best.cards {
+            rank_count[card.rank().index()] += 1;
+        }
+        let is_wheel = matches!(
+            best.value.category(),
+            HandCategory::Straight | HandCategory::StraightFlush
+        ) && best.value.tiebreak_ranks()[0] == Rank::Five;
+        best.cards.sort_by_key(|card| {
+            let rank_index = if is_wheel {
+                card.rank().index()
+            } else {
+                ace_high_index(card.rank())
+            };
+            std::cmp::Reverse((rank_count[card.rank().index()], rank_index))
+        });
+        Some(best)
+    }
+
+    /// The cards that make the category, such as the two cards of a pair.
+    pub fn made_cards(&self) -> &[Card] {
+        &self.cards[..self.value.category().num_made_cards()]
+    }
+
+    /// The cards that only break ties within the category.
+    pub fn kickers(&self) -> &[Card] {
+        &self.cards[self.value.category().num_made_cards()..]
+    }
+}
+
+/// The description of the hand followed by its cards, for example
+/// "Flush, Ace-high (A♥ J♥ 9♥ 6♥ 2♥)".
+impl fmt::Display for BestFive {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cards = self
+            .cards
+            .iter()
+            .map(|card| card.to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+        write!(f, "{} ({cards})", self.value)
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use crate::card::Rank;
+    use crate::deck::Deck;
+    use crate::hand_value::BestFive;
+    use crate::hand_value::HandCategory;
+    use crate::hand_value::HandValue;
+
+    fn value(deck: &Deck, names: &[&str]) -> HandValue {
+        HandValue::from_cards(&deck.draw_cards(names).unwrap())
+    }
+
+    #[test]
+    fn categories() {
+        let deck = Deck::new();
+        let cases = [
+            (vec!["5♣", "8♣", "3♣", "K♠", "7♦"], HandCategory::HighCard),
+            (vec!["5♣", "8♣", "8♠", "7♣", "9♦"], HandCategory::Pair),
+            (vec!["5♣", "4♦", "7♣", "7♦", "4♥"], HandCategory::TwoPair),
+            (
+                vec!["5♣", "4♦", "7♣", "5♦", "5♥"],
+                HandCategory::ThreeOfAKind,
+            ),
+            (vec!["T♦", "J♠", "Q♠", "K♦", "A♥"], HandCategory::Straight),
+            (vec!["5♦", "2♠", "3♠", "4♦", "A♥"], HandCategory::Straight),
+            (vec!["5♣", "9♣", "8♣", "7♣", "2♣"], HandCategory::Flush),
+            (vec!["4♦", "5♦", "5♣", "4♣", "5♥"], HandCategory::FullHouse),
+            (
+                vec!["4♦", "4♠", "5♣", "4♣", "4♥"],
+                HandCategory::FourOfAKind,
+            ),
+            (
+                vec!["9♥", "7♥", "8♥", "T♥", "J♥"],
+                HandCategory::StraightFlush,
+            ),
+            (
+                vec!["Q♥", "Q♣", "Q♥", "Q♠", "Q♦"],
+                HandCategory::FiveOfAKind,
+            ),
+            (vec!["Q♥", "Q♥", "Q♥", "Q♥", "Q♥"], HandCategory::FlushFive),
+            // Seven cards: two sets of trips make a full house,
+            (
+                vec!["4♦", "4♠", "9♣", "4♣", "9♥", "9♦", "K♥"],
+                HandCategory::FullHouse,
+            ),
+            // and a gap below a straight does not hide it.
+            (
+                vec!["2♦", "3♠", "6♣", "7♣", "8♥", "9♦", "T♥"],
+                HandCategory::Straight,
+            ),
+        ];
+        for (names, category) in cases {
+            assert_eq!(value(&deck, &names).category(), category, "{names:?}");
+        }
+    }
+
+    #[test]
+    fn comparisons() {
+        let deck = Deck::new();
+        // Ace-high straight beats king-high, which beats the wheel.
+        assert!(
+            value(&deck, &["T♦", "J♠", "Q♠", "K♦", "A♥"])
+                > value(&deck, &["T♦", "J♠", "Q♠", "K♦", "9♥"])
+        );
+        assert!(
+            value(&deck, &["6♦", "2♠", "3♠", "4♦", "5♥"])
+                > value(&deck, &["5♦", "2♠", "3♠", "4♦", "A♥"])
+        );
+        // Kickers decide between equal pairs, and suits never matter.
+        assert!(
+            value(&deck, &["8♣", "8♠", "A♣", "3♦", "2♦"])
+                > value(&deck, &["8♦", "8♥", "K♣", "Q♦", "J♦"])
+        );
+        assert_eq!(
+            value(&deck, &["8♣", "8♠", "A♣", "3♦", "2♦"]),
+            value(&deck, &["8♦", "8♥", "A♦", "3♣", "2♣"])
+        );
+        // Only the best five of seven cards count.
+        assert_eq!(
+            value(&deck, &["8♣", "8♠", "A♣", "K♦", "Q♦", "3♠", "2♠"]),
+            value(&deck, &["8♦", "8♥", "A♦", "K♣", "Q♣", "4♠", "2♣"])
+        );
+    }
+
+    #[test]
+    fn tiebreak_ranks() {
+        let deck = Deck::new();
+        let full_house = value(&deck, &["K♦", "7♠", "K♣", "7♣", "K♥", "A♦"]);
+        assert_eq!(full_house.category(), HandCategory::FullHouse);
+        assert_eq!(full_house.tiebreak_ranks(), vec![Rank::King, Rank::Seven]);
+        let wheel = value(&deck, &["5♦", "2♠", "3♠", "4♦", "A♥"]);
+        assert_eq!(wheel.tiebreak_ranks(), vec![Rank::Five]);
+    }
+
+    #[test]
+    fn best_five_cards() {
+        let deck = Deck::new();
+        let best = |names: &[&str]| BestFive::from_cards(&deck.draw_cards(names).unwrap()).unwrap();
+        let cards = |names: &[&str]| deck.draw_cards(names).unwrap();
+
+        let flush = best(&["2♥", "K♠", "A♥", "9♥", "J♥", "6♥", "3♥"]);
+        assert_eq!(flush.value.category(), HandCategory::Flush);
+        assert_eq!(flush.cards.to_vec(), cards(&["A♥", "J♥", "9♥", "6♥", "3♥"]));
+        assert!(flush.kickers().is_empty());
+
+        let two_pair = best(&["8♣", "K♦", "8♥", "2♣", "K♠", "2♦", "9♣"]);
+        assert_eq!(
+            two_pair.made_cards().to_vec(),
+            cards(&["K♦", "K♠", "8♣", "8♥"])
+        );
+        assert_eq!(two_pair.kickers().to_vec(), cards(&["9♣"]));
+
+        let pair = best(&["4♠", "Q♦", "7♣", "Q♣", "A♦", "T♥", "2♠"]);
+        assert_eq!(pair.made_cards().to_vec(), cards(&["Q♦", "Q♣"]));
+        assert_eq!(pair.kickers().to_vec(), cards(&["A♦", "T♥", "7♣"]));
+
+        let full_house = best(&["7♣", "K♦", "7♥", "K♠", "7♠", "K♣"]);
+        assert_eq!(
+            full_house.cards.to_vec(),
+            cards(&["K♦", "K♠", "K♣", "7♣", "7♥"])
+        );
+
+        let wheel = best(&["A♠", "3♦", "5♣", "2♥", "4♦", "9♠", "K♣"]);
+        assert_eq!(wheel.cards.to_vec(), cards(&["5♣", "4♦", "3♦", "2♥", "A♠"]));
+
+        let high_card = best(&["4♠", "Q♦", "7♣", "9♣", "2♦"]);
+        assert_eq!(high_card.made_cards().to_vec(), cards(&["Q♦"]));
+        assert_eq!(high_card.kickers().len(), 4);
+        assert!(BestFive::from_cards(&cards(&["A♠", "A♦", "A♣", "A♥"])).is_none());
+    }
+
+    #[test]
+    fn descriptions() {
+        let deck = Deck::new();
+        let cases = [
+            (vec!["5♣", "8♣", "3♣", "K♠", "7♦"], "High card, King"),
+            (vec!["5♣", "8♣", "8♠", "7♣", "9♦"], "Pair, Eights"),
+            (
+                vec!["5♣", "4♦", "7♣", "7♦", "4♥"],
+                "Two pair, Sevens and Fours",
+            ),
+            (vec!["6♣", "4♦", "7♣", "6♦", "6♥"], "Three of a kind, Sixes"),
+            (vec!["5♦", "2♠", "3♠", "4♦", "A♥"], "Straight, Five-high"),
+            (vec!["5♣", "9♣", "8♣", "7♣", "2♣"], "Flush, Nine-high"),
+            (
+                vec!["K♦", "7♦", "K♣", "7♣", "K♥"],
+                "Full house, Kings full of Sevens",
+            ),
+            (vec!["Q♦", "Q♠", "Q♣", "2♣", "Q♥"], "Four of a kind, Queens"),
+            (
+                vec!["9♥", "8♥", "7♥", "6♥", "5♥"],
+                "Straight flush, Nine-high",
+            ),
+            (vec!["T♠", "J♠", "Q♠", "K♠", "A♠"], "Royal flush"),
+        ];
+        for (names, description) in cases {
+            assert_eq!(value(&deck, &names).to_string(), description);
+        }
+        let flush = BestFive::from_cards(
+            &deck
+                .draw_cards(&["2♥", "K♠", "A♥", "9♥", "J♥", "6♥"])
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(flush.to_string(), "Flush, Ace-high (A♥ J♥ 9♥ 6♥ 2♥)");
+    }
+}
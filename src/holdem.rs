@@ -0,0 +1,460 @@
+use crate::aggregate_score::{enumerate_aggregate_scores_with_known, AggregateScore, CountingMode};
+use crate::card::Card;
+use crate::deck::remaining_cards;
+use crate::error::{PokerError, Result};
+use crate::hand::check_unique;
+use crate::hand_score::HandScore;
+use crate::hand_stats::HandStats;
+use crate::hand_value::{HandCategory, HandValue};
+use crate::parallel::{batch_rng, parallel_batches};
+use itertools::Itertools;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::fmt;
+
+/// Texas hold'em deals two hole cards to each player, and five community
+/// cards: three on the flop, one on the turn and one on the river.
+pub const HOLE_CARDS: usize = 2;
+pub const BOARD_SIZE: usize = 5;
+
+/// Everything known about a hold'em hand in progress: the hole cards of each
+/// player that is still in the hand (which may be empty for an opponent with
+/// unknown cards), the community cards dealt so far, and any cards known to
+/// be out of play. Any missing cards are dealt at random.
+#[derive(Default, PartialEq, Debug, Clone)]
+pub struct HoldemTable {
+    pub players: Vec<Vec<Card>>,
+    pub board: Vec<Card>,
+    pub dead_cards: Vec<Card>,
+}
+
+#[derive(Default, PartialEq, Debug, Clone)]
+pub struct HoldemResults {
+    pub num_samples: u64,
+    /// Number of showdowns won by each player, where ties split the pot evenly.
+    pub wins: Vec<f64>,
+}
+
+impl HoldemTable {
+    fn known_cards(&self) -> Vec<Card> {
+        self.players
+            .iter()
+            .flatten()
+            .chain(self.board.iter())
+            .chain(self.dead_cards.iter())
+            .copied()
+            .collect()
+    }
+
+    /// Number of cards that must still be dealt to finish the hand.
+    fn num_unknown_cards(&self) -> usize {
+        BOARD_SIZE - self.board.len()
+            + self
+                .players
+                .iter()
+                .map(|hole_cards| HOLE_CARDS - hole_cards.len())
+                .sum::<usize>()
+    }
+
+    /// Checks that the table could occur in a real game: every card is dealt
+    /// at most once, no player or board has too many cards, and there are
+    /// enough cards left in the deck to finish the hand.
+    pub fn validate(&self) -> Result<()> {
+        if self.players.is_empty() {
+            return Err(PokerError::NoPlayers);
+        }
+        if self.board.len() > BOARD_SIZE {
+            return Err(PokerError::InvalidCardCount {
+                what: "the board",
+                expected: "at most 5",
+                found: self.board.len(),
+            });
+        }
+        if let Some(hole_cards) = self
+            .players
+            .iter()
+            .find(|hole_cards| hole_cards.len() > HOLE_CARDS)
+        {
+            return Err(PokerError::InvalidCardCount {
+                what: "each player",
+                expected: "at most 2",
+                found: hole_cards.len(),
+            });
+        }
+        let known_cards = self.known_cards();
+        check_unique(&known_cards)?;
+        if self.num_unknown_cards() > Card::NUM_CARDS - known_cards.len() {
+            return Err(PokerError::NotEnoughCards);
+        }
+        Ok(())
+    }
+
+    /// Returns true if the table could occur in a real game, see `validate`.
+    pub fn is_valid(&self) -> bool {
+        self.validate().is_ok()
+    }
+
+    /// Hands of every player once the board is complete, with the unknown
+    /// cards taken from `dealt`: first the rest of the board, and then the
+    /// missing hole cards of each player in turn.
+    fn showdown_values(&self, dealt: &[Card]) -> Vec<HandValue> {
+        let (board_cards, mut dealt) = dealt.split_at(BOARD_SIZE - self.board.len());
+        let mut cards: Vec<Card> = self.board.iter().chain(board_cards).copied().collect();
+        self.players
+            .iter()
+            .map(|hole_cards| {
+                cards.truncate(BOARD_SIZE);
+                cards.extend_from_slice(hole_cards);
+                let (missing, rest) = dealt.split_at(HOLE_CARDS - hole_cards.len());
+                cards.extend_from_slice(missing);
+                dealt = rest;
+                HandValue::from_cards(&cards)
+            })
+            .collect()
+    }
+}
+
+/// Splits a pot of one between the players with the best hand.
+pub(crate) fn award_pot(values: &[HandValue], wins: &mut [f64]) {
+    let best = values.iter().max().unwrap();
+    let num_winners = values.iter().filter(|value| *value == best).count();
+    for (value, win) in values.iter().zip(wins.iter_mut()) {
+        if value == best {
+            *win += 1.0 / (num_winners as f64);
+        }
+    }
+}
+
+impl HoldemResults {
+    pub fn new(num_players: usize) -> HoldemResults {
+        HoldemResults {
+            num_samples: 0,
+            wins: vec![0.0; num_players],
+        }
+    }
+
+    pub fn insert(&mut self, other: &HoldemResults) {
+        self.num_samples += other.num_samples;
+        for (wins, other_wins) in self.wins.iter_mut().zip(other.wins.iter()) {
+            *wins += other_wins;
+        }
+    }
+
+    /// Share of the pot that each player wins on average.
+    pub fn equity(&self) -> Vec<f64> {
+        self.wins
+            .iter()
+            .map(|wins| wins / (self.num_samples as f64))
+            .collect()
+    }
+}
+
+impl fmt::Display for HoldemResults {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "HoldemResults: {} samples", self.num_samples)?;
+        for (index, equity) in self.equity().iter().enumerate() {
+            write!(f, "\nPlayer {index}: equity {:>7.3}%", 100.0 * equity)?;
+        }
+        Ok(())
+    }
+}
+
+/// Deals out the rest of the hand `num_samples` times, and records who wins
+/// each showdown. Fails if the table is not valid.
+pub fn simulate_holdem<R: Rng>(
+    rng: &mut R,
+    table: &HoldemTable,
+    num_samples: u32,
+) -> Result<HoldemResults> {
+    table.validate()?;
+    let mut stub = remaining_cards(&table.known_cards());
+    let num_unknown_cards = table.num_unknown_cards();
+    let mut results = HoldemResults::new(table.players.len());
+    results.num_samples = num_samples as u64;
+    for _ in 0..num_samples {
+        let (dealt, _) = stub.partial_shuffle(rng, num_unknown_cards);
+        award_pot(&table.showdown_values(dealt), &mut results.wins);
+    }
+    Ok(results)
+}
+
+/// Multi-threaded version of `simulate_holdem`, with one batch of samples
+/// per thread. Each batch draws from a generator derived from `seed`.
+pub fn parallel_simulate_holdem(
+    table: &HoldemTable,
+    num_samples: u32,
+    num_threads: u32,
+    seed: u64,
+) -> Result<HoldemResults> {
+    table.validate()?;
+    parallel_batches(
+        num_samples,
+        num_threads,
+        HoldemResults::new(table.players.len()),
+        |batch, batch_size| simulate_holdem(&mut batch_rng(seed, batch), table, batch_size),
+        HoldemResults::insert,
+    )
+}
+
+/// Exact equity, found by dealing every possible completion of the board.
+/// This needs the hole cards of every player, and fails if any are missing
+/// or the table is not valid. From the flop on this is very fast, while a
+/// preflop matchup deals over a million boards.
+pub fn enumerate_holdem(table: &HoldemTable) -> Result<HoldemResults> {
+    table.validate()?;
+    if let Some(hole_cards) = table
+        .players
+        .iter()
+        .find(|hole_cards| hole_cards.len() != HOLE_CARDS)
+    {
+        return Err(PokerError::InvalidCardCount {
+            what: "each player",
+            expected: "2",
+            found: hole_cards.len(),
+        });
+    }
+    let stub = remaining_cards(&table.known_cards());
+    let mut results = HoldemResults::new(table.players.len());
+    for board_cards in stub
+        .iter()
+        .copied()
+        .combinations(BOARD_SIZE - table.board.len())
+    {
+        results.num_samples += 1;
+        award_pot(&table.showdown_values(&board_cards), &mut results.wins);
+    }
+    Ok(results)
+}
+
+/// Categories made by the hole cards and the board on each street, from the
+/// five cards on the flop to all seven on the river. Each score counts the
+/// boards that make the category or better, like [`AggregateScore`].
+#[derive(Default, PartialEq, Debug)]
+pub struct StreetProbabilities {
+    pub flop: AggregateScore,
+    pub turn: AggregateScore,
+    pub river: AggregateScore,
+}
+
+/// Number of cards in a player's hand on the flop, turn and river.
+const STREET_HAND_SIZES: [usize; 3] = [HOLE_CARDS + 3, HOLE_CARDS + 4, HOLE_CARDS + BOARD_SIZE];
+
+/// Checks that there are two different hole cards, and a board of at most
+/// five other cards.
+pub(crate) fn validate_street_hand(hole_cards: &[Card], board: &[Card]) -> Result<()> {
+    if hole_cards.len() != HOLE_CARDS {
+        return Err(PokerError::InvalidCardCount {
+            what: "the hole cards",
+            expected: "2",
+            found: hole_cards.len(),
+        });
+    }
+    HoldemTable {
+        players: vec![hole_cards.to_vec()],
+        board: board.to_vec(),
+        dead_cards: vec![],
+    }
+    .validate()
+}
+
+/// Exact probabilities of making each category by every street, given the
+/// hole cards and any community cards dealt so far. Streets that are already
+/// dealt have a single outcome. Fails if the cards are not valid.
+pub fn street_probabilities(hole_cards: &[Card], board: &[Card]) -> Result<StreetProbabilities> {
+    validate_street_hand(hole_cards, board)?;
+    // The hole cards and the part of the board that is dealt by each street.
+    let known = |hand_size: usize| -> Vec<Card> {
+        let num_board_cards = board.len().min(hand_size - HOLE_CARDS);
+        hole_cards
+            .iter()
+            .chain(board[..num_board_cards].iter())
+            .copied()
+            .collect()
+    };
+    let [flop, turn, river] = STREET_HAND_SIZES;
+    let mode = CountingMode::Cumulative;
+    Ok(StreetProbabilities {
+        flop: enumerate_aggregate_scores_with_known::<{ HOLE_CARDS + 3 }>(&known(flop), 1, mode)?,
+        turn: enumerate_aggregate_scores_with_known::<{ HOLE_CARDS + 4 }>(&known(turn), 1, mode)?,
+        river: enumerate_aggregate_scores_with_known::<{ HOLE_CARDS + BOARD_SIZE }>(
+            &known(river),
+            1,
+            mode,
+        )?,
+    })
+}
+
+/// Sampled version of `street_probabilities`. Each sample deals the rest of
+/// the board once, and scores the hand on every street.
+pub fn sample_street_probabilities<R: Rng>(
+    rng: &mut R,
+    hole_cards: &[Card],
+    board: &[Card],
+    num_samples: u32,
+) -> Result<StreetProbabilities> {
+    validate_street_hand(hole_cards, board)?;
+    let known: Vec<Card> = hole_cards.iter().chain(board.iter()).copied().collect();
+    let mut stub = remaining_cards(&known);
+    let mut results = StreetProbabilities::default();
+    for _ in 0..num_samples {
+        let (dealt, _) = stub.partial_shuffle(rng, BOARD_SIZE - board.len());
+        let mut cards = known.iter().chain(dealt.iter());
+        let mut hand_stats = HandStats::default();
+        for (hand_size, scores) in
+            STREET_HAND_SIZES
+                .iter()
+                .zip([&mut results.flop, &mut results.turn, &mut results.river])
+        {
+            while hand_stats.count_cards() < *hand_size {
+                hand_stats.insert(*cards.next().unwrap());
+            }
+            scores.insert(&HandScore::from(&hand_stats));
+        }
+    }
+    Ok(results)
+}
+
+impl fmt::Display for StreetProbabilities {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let n_pad_name = "three_of_a_kind".len();
+        write!(
+            f,
+            "StreetProbabilities:\n  {:<n_pad_name$}  {:>8}  {:>8}  {:>8}",
+            "", "Flop", "Turn", "River"
+        )?;
+        let streets = [&self.flop, &self.turn, &self.river];
+        let percent = |score: &AggregateScore, value: u64| {
+            100.0 * value as f64 / score[HandCategory::HighCard].max(1) as f64
+        };
+        for category in HandCategory::ALL {
+            write!(f, "\n  {:<n_pad_name$}", category.snake_case_name())?;
+            for score in streets {
+                write!(f, "  {:>7.3}%", percent(score, score[category]))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use crate::deck::Deck;
+    use crate::hand_value::HandCategory;
+    use crate::holdem::enumerate_holdem;
+    use crate::holdem::parallel_simulate_holdem;
+    use crate::holdem::sample_street_probabilities;
+    use crate::holdem::simulate_holdem;
+    use crate::holdem::street_probabilities;
+    use crate::holdem::HoldemTable;
+    use rand::SeedableRng;
+
+    fn table(deck: &Deck, players: &[&[&str]], board: &[&str]) -> HoldemTable {
+        HoldemTable {
+            players: players
+                .iter()
+                .map(|names| deck.draw_cards(names).unwrap())
+                .collect(),
+            board: deck.draw_cards(board).unwrap(),
+            dead_cards: vec![],
+        }
+    }
+
+    #[test]
+    fn invalid_tables() {
+        let deck = Deck::new();
+        let mut rng = rand::rngs::StdRng::seed_from_u64(9);
+        let duplicate = table(&deck, &[&["A♠", "A♥"], &["A♠"]], &[]);
+        assert!(simulate_holdem(&mut rng, &duplicate, 10).is_err());
+        let too_many = table(&deck, &[&["A♠", "A♥", "A♦"]], &[]);
+        assert!(simulate_holdem(&mut rng, &too_many, 10).is_err());
+        let unknown = table(&deck, &[&["A♠", "A♥"], &[]], &["2♣", "7♦", "9♥"]);
+        assert!(enumerate_holdem(&unknown).is_err());
+    }
+
+    /// Aces against kings on a dry flop, with 45 * 44 / 2 = 990 turn and river
+    /// combinations to come.
+    #[test]
+    fn exact_flop_equity() {
+        let deck = Deck::new();
+        let table = table(&deck, &[&["A♠", "A♥"], &["K♠", "K♥"]], &["2♣", "7♦", "9♥"]);
+        let results = enumerate_holdem(&table).unwrap();
+        assert_eq!(results.num_samples, 990);
+        // Kings win with a king and no ace, 2 * 41 + 1 ways, since neither
+        // player can make a straight or a flush.
+        assert_eq!(results.wins[1], 83.0);
+        assert_eq!(results.wins[0], 990.0 - 83.0);
+    }
+
+    #[test]
+    fn simulation_matches_enumeration() {
+        let deck = Deck::new();
+        let mut rng = rand::rngs::StdRng::seed_from_u64(4242);
+        let table = table(
+            &deck,
+            &[&["A♠", "K♠"], &["7♦", "7♣"]],
+            &["Q♠", "7♠", "2♥", "3♦"],
+        );
+        let exact = enumerate_holdem(&table).unwrap().equity();
+        let sampled = simulate_holdem(&mut rng, &table, 20000).unwrap().equity();
+        for (exact, sampled) in exact.iter().zip(sampled.iter()) {
+            assert!((exact - sampled).abs() < 0.02);
+        }
+    }
+
+    #[test]
+    fn parallel_simulation() {
+        let deck = Deck::new();
+        let table = table(&deck, &[&["A♠", "A♥"], &[], &[]], &[]);
+        let results = parallel_simulate_holdem(&table, 1001, 4, 17).unwrap();
+        assert_eq!(results.num_samples, 1001);
+        assert_eq!(
+            results,
+            parallel_simulate_holdem(&table, 1001, 4, 17).unwrap()
+        );
+        let total: f64 = results.equity().iter().sum();
+        assert!((total - 1.0).abs() < 1e-9);
+        assert!(results.equity()[0] > 0.6);
+        assert_eq!(
+            results.to_string().lines().next(),
+            Some("HoldemResults: 1001 samples")
+        );
+        assert!(results
+            .to_string()
+            .contains("\nPlayer 0: equity  73.177%\n"));
+    }
+
+    /// Suited hole cards: the flop makes a flush with 11 * 10 * 9 / 6 = 165 of
+    /// the C(50, 3) = 19600 flops, and the river is the familiar 6.4%.
+    #[test]
+    fn street_probabilities_for_suited_cards() {
+        let deck = Deck::new();
+        let hole_cards = deck.draw_cards(&["A♥", "K♥"]).unwrap();
+        let exact = street_probabilities(&hole_cards, &[]).unwrap();
+        assert_eq!(exact.flop[HandCategory::HighCard], 19600);
+        assert_eq!(exact.flop[HandCategory::Flush], 165);
+        assert_eq!(exact.turn[HandCategory::HighCard], 230300);
+        let river_flush =
+            exact.river[HandCategory::Flush] as f64 / exact.river[HandCategory::HighCard] as f64;
+        assert!((river_flush - 0.065).abs() < 0.002);
+        // Once the flop is known, it has a single outcome.
+        let flop = deck.draw_cards(&["2♥", "7♥", "9♣"]).unwrap();
+        let exact = street_probabilities(&hole_cards, &flop).unwrap();
+        assert_eq!(exact.flop[HandCategory::HighCard], 1);
+        assert_eq!(exact.flop[HandCategory::Flush], 0);
+        assert_eq!(exact.turn[HandCategory::Flush], 9);
+        assert_eq!(exact.river[HandCategory::HighCard], 1081);
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        let sampled = sample_street_probabilities(&mut rng, &hole_cards, &flop, 10000).unwrap();
+        assert_eq!(sampled.flop[HandCategory::HighCard], 10000);
+        assert_eq!(sampled.flop[HandCategory::Flush], 0);
+        let river_flush = sampled.river[HandCategory::Flush] as f64 / 10000.0;
+        assert!((river_flush - 0.35).abs() < 0.02);
+        assert!(street_probabilities(&flop, &hole_cards).is_err());
+        let text = exact.to_string();
+        assert!(text.contains("\n  high_card        100.000%  100.000%  100.000%\n"));
+        assert!(text.contains("\n  flush              0.000%   19.149%   34.968%\n"));
+    }
+}
@@ -1,6 +1,25 @@
 pub mod aggregate_score;
+pub mod board_texture;
 pub mod card;
+pub mod card_subsets;
+pub mod chinese_poker;
 pub mod deck;
+pub mod draw_poker;
+pub mod equity_histogram;
+pub mod error;
 pub mod hand;
 pub mod hand_score;
+pub mod hand_statistic;
 pub mod hand_stats;
+pub mod hand_strength;
+pub mod hand_value;
+pub mod holdem;
+pub mod nuts;
+pub mod outs;
+mod parallel;
+pub mod pineapple;
+pub mod preflop;
+pub mod stud;
+pub mod suit_isomorphism;
+pub mod table_games;
+pub mod video_poker;
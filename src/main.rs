@@ -1,37 +1,103 @@
-// Note: Each of the utility files must be added here as a
-// module so that it can be properly included in others.
-mod aggregate_score;
+// The binary only adds argument parsing and output on top of the
+// `poker_stats` library.
 mod args;
-mod card;
-mod deck;
-mod hand;
-mod hand_score;
-mod hand_stats;
 mod output;
 
 use crate::args::{CommandsEnum, PokerArgs, StatisticsSampleParameters};
 use clap::Parser;
-use output::{draw_and_display_hand_wrapper, print_sorted_deck, sample_and_display_statistics};
-/// Simple demo for the `poker-stats` crate. For now it
-/// does not support any arguments. It will do three things:
-/// (1) print out the cards in a sorted deck
-/// For each of two randomly drawn hands:
-/// (2) print out the hand
-/// (3) compute and print the card stats
-fn main() {
+use output::{
+    analyze_and_display_video_poker, compute_and_save_equity_histograms,
+    compute_and_save_preflop_table, display_street_probabilities, draw_and_display_hand_wrapper,
+    find_and_display_nuts, find_and_display_outs, look_up_preflop_equity, print_sorted_deck,
+    sample_and_display_statistics, score_and_display_cards, simulate_and_display_draw_poker,
+};
+use poker_stats::aggregate_score::CountingMode;
+use poker_stats::error::PokerError;
+use std::process::ExitCode;
+
+/// Command line front end for the `poker-stats` crate. Each subcommand runs
+/// one part of the library, see `--help` for the full list:
+/// - `draw-hand`, `score` and `sorted-deck` deal, describe and list cards.
+/// - `statistics` and `draw-poker` sample category counts over many hands.
+/// - `video-poker` computes a machine's return or the best hold for a deal.
+/// - `outs`, `street-odds`, `nuts`, `preflop-table`, `preflop` and
+///   `equity-histograms` analyze hold'em hands and boards.
+///
+/// With no subcommand, it draws and scores a random five card hand.
+///
+/// Errors are printed to stderr. The exit code is 2 for invalid input, the
+/// same as for invalid arguments, and 1 if a file could not be read or
+/// written.
+fn main() -> ExitCode {
     let args: PokerArgs = PokerArgs::parse();
     let rng = rand::thread_rng();
     // Matching the command
-    match &args.command {
+    let result = match &args.command {
         Some(CommandsEnum::DrawHand { hands_size }) => {
             draw_and_display_hand_wrapper(*hands_size, rng)
         }
+        Some(CommandsEnum::Score { cards }) => score_and_display_cards(cards),
         Some(CommandsEnum::Statistics(StatisticsSampleParameters {
             hands_number,
             number_of_samples,
             number_of_threads,
-        })) => sample_and_display_statistics(*hands_number, *number_of_samples, *number_of_threads),
-        Some(CommandsEnum::SortedDeck) => print_sorted_deck(),
+            decks,
+            exclusive,
+        })) => sample_and_display_statistics(
+            *hands_number,
+            *number_of_samples,
+            *number_of_threads,
+            *decks as usize,
+            if *exclusive {
+                CountingMode::Exclusive
+            } else {
+                CountingMode::Cumulative
+            },
+        ),
+        Some(CommandsEnum::SortedDeck) => {
+            print_sorted_deck();
+            Ok(())
+        }
+        Some(CommandsEnum::DrawPoker {
+            number_of_samples,
+            number_of_threads,
+        }) => simulate_and_display_draw_poker(*number_of_samples, *number_of_threads),
+        Some(CommandsEnum::VideoPoker { game, hand }) => {
+            analyze_and_display_video_poker(*game, hand)
+        }
+        Some(CommandsEnum::Outs {
+            hole_cards,
+            board,
+            opponent,
+        }) => find_and_display_outs(hole_cards, board, opponent),
+        Some(CommandsEnum::StreetOdds {
+            hole_cards,
+            board,
+            samples,
+        }) => display_street_probabilities(hole_cards, board, *samples),
+        Some(CommandsEnum::Nuts { board, count }) => find_and_display_nuts(board, *count),
+        Some(CommandsEnum::PreflopTable {
+            number_of_samples,
+            file,
+        }) => compute_and_save_preflop_table(*number_of_samples, file),
+        Some(CommandsEnum::EquityHistograms {
+            number_of_boards,
+            file,
+            bins,
+        }) => compute_and_save_equity_histograms(*number_of_boards, file, *bins),
+        Some(CommandsEnum::Preflop { file, hand, versus }) => {
+            look_up_preflop_equity(file, hand, versus)
+        }
         None => draw_and_display_hand_wrapper(5, rng),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {error}");
+            match error {
+                PokerError::Io(_) => ExitCode::from(1),
+                _ => ExitCode::from(2),
+            }
+        }
     }
 }
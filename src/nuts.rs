@@ -0,0 +1,124 @@
+use crate::card::Card;
+use crate::deck::remaining_cards;
+use crate::error::{PokerError, Result};
+use crate::hand_value::HandValue;
+use crate::holdem::{HoldemTable, HOLE_CARDS};
+use itertools::Itertools;
+use std::fmt;
+
+/// Most holdings to list for one hand before only giving the count.
+const MAX_LISTED_HOLDINGS: usize = 16;
+
+/// One of the best possible hands on a board, and every pair of hole cards
+/// that makes it.
+#[derive(PartialEq, Debug, Clone)]
+pub struct NutHand {
+    pub value: HandValue,
+    pub holdings: Vec<[Card; HOLE_CARDS]>,
+}
+
+/// The `num_hands` best hands that can be made on a hold'em board, from the
+/// nuts down: looks at every two hole cards from the rest of the deck, and
+/// groups those that make equal hands. Fails if the board is not a flop,
+/// turn or river of different cards.
+pub fn find_nuts(board: &[Card], num_hands: usize) -> Result<Vec<NutHand>> {
+    HoldemTable {
+        players: vec![vec![]],
+        board: board.to_vec(),
+        dead_cards: vec![],
+    }
+    .validate()?;
+    if board.len() < 3 {
+        return Err(PokerError::InvalidCardCount {
+            what: "the board",
+            expected: "3 to 5",
+            found: board.len(),
+        });
+    }
+    let mut cards = board.to_vec();
+    let mut holdings: Vec<(HandValue, [Card; HOLE_CARDS])> = remaining_cards(board)
+        .into_iter()
+        .combinations(HOLE_CARDS)
+        .map(|hole_cards| {
+            cards.truncate(board.len());
+            cards.extend_from_slice(&hole_cards);
+            (
+                HandValue::from_cards(&cards),
+                [hole_cards[0], hole_cards[1]],
+            )
+        })
+        .collect();
+    holdings.sort_by_key(|(value, _)| std::cmp::Reverse(*value));
+    Ok(holdings
+        .chunk_by(|a, b| a.0 == b.0)
+        .take(num_hands)
+        .map(|group| NutHand {
+            value: group[0].0,
+            holdings: group.iter().map(|(_, hole_cards)| *hole_cards).collect(),
+        })
+        .collect())
+}
+
+impl fmt::Display for NutHand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} holding(s)", self.value, self.holdings.len())?;
+        if self.holdings.len() <= MAX_LISTED_HOLDINGS {
+            let names = self
+                .holdings
+                .iter()
+                .map(|hole_cards| format!("{}{}", hole_cards[0], hole_cards[1]))
+                .collect::<Vec<_>>()
+                .join(" ");
+            write!(f, "\n  {names}")?;
+        }
+        Ok(())
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use crate::deck::Deck;
+    use crate::hand_value::HandCategory;
+    use crate::nuts::find_nuts;
+
+    #[test]
+    fn nuts_on_the_river() {
+        let deck = Deck::new();
+        let board = deck.draw_cards(&["K♥", "Q♥", "7♥", "7♣", "2♦"]).unwrap();
+        let nuts = find_nuts(&board, 3).unwrap();
+        assert_eq!(nuts.len(), 3);
+        // Quad sevens, then kings full and queens full from a pocket pair.
+        assert_eq!(nuts[0].value.category(), HandCategory::FourOfAKind);
+        assert_eq!(
+            nuts[0].holdings,
+            vec![deck.draw_hand(&["7♦", "7♠"]).unwrap().cards]
+        );
+        assert_eq!(nuts[1].value.category(), HandCategory::FullHouse);
+        assert_eq!(nuts[1].holdings.len(), 3);
+        assert_eq!(nuts[2].holdings.len(), 3);
+        assert!(nuts[0].value > nuts[1].value && nuts[1].value > nuts[2].value);
+        assert_eq!(
+            nuts[0].to_string(),
+            "Four of a kind, Sevens: 1 holding(s)\n  7♦7♠"
+        );
+    }
+
+    /// The royal flush draw on a flop: only one holding makes the nuts.
+    #[test]
+    fn nuts_on_the_flop() {
+        let deck = Deck::new();
+        let board = deck.draw_cards(&["A♠", "K♠", "Q♠"]).unwrap();
+        let nuts = find_nuts(&board, 2).unwrap();
+        assert_eq!(nuts[0].value.category(), HandCategory::StraightFlush);
+        assert_eq!(
+            nuts[0].holdings,
+            vec![deck.draw_hand(&["T♠", "J♠"]).unwrap().cards]
+        );
+        assert_eq!(nuts[1].value.category(), HandCategory::Flush);
+        assert!(find_nuts(&board[..2], 1).is_err());
+        let duplicate = deck.draw_cards(&["A♠", "K♠", "A♠"]).unwrap();
+        assert!(find_nuts(&duplicate, 1).is_err());
+    }
+}
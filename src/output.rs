@@ -1,27 +1,86 @@
-use crate::aggregate_score::parallel_sample_aggregate_scores;
-use crate::card::Card;
-use crate::hand::Hand;
-use crate::hand_score::HandScore;
-use crate::hand_stats::HandStats;
+use crate::args::VideoPokerGame;
+use poker_stats::aggregate_score::{parallel_sample_aggregate_scores, CountingMode};
+use poker_stats::card::Card;
+use poker_stats::card::Suit;
+use poker_stats::deck::Deck;
+use poker_stats::draw_poker::{
+    parallel_simulate_draw, DiscardStrategy, DrawToFlush, Holds, KeepPairs,
+};
+use poker_stats::equity_histogram::{save_csv, starting_hand_histograms};
+use poker_stats::error::{PokerError, Result};
+use poker_stats::hand::{check_unique, Hand};
+use poker_stats::hand_score::HandScore;
+use poker_stats::hand_stats::HandStats;
+use poker_stats::hand_value::{BestFive, HandValue};
+use poker_stats::holdem::{sample_street_probabilities, street_probabilities, HoldemTable};
+use poker_stats::nuts::find_nuts;
+use poker_stats::outs::find_outs;
+use poker_stats::preflop::{PreflopTable, StartingHand, MAX_OPPONENTS};
+use poker_stats::video_poker::{
+    analyze_holds, best_hold, return_to_player, DeucesWild, JacksOrBetter, PayTable,
+};
 use rand::rngs::ThreadRng;
+use std::io;
+
+/// Adds the file name to an io error, so that the message says which file
+/// could not be read or written.
+fn file_error(file: &str) -> impl FnOnce(io::Error) -> PokerError + '_ {
+    move |error| PokerError::Io(io::Error::new(error.kind(), format!("{file}: {error}")))
+}
+
+fn draw_cards(names: &[String]) -> Result<Vec<Card>> {
+    let names: Vec<&str> = names.iter().map(|name| name.as_str()).collect();
+    Deck::new().draw_cards(&names)
+}
+
+fn invalid_hand_size(found: usize) -> PokerError {
+    PokerError::InvalidCardCount {
+        what: "a hand",
+        expected: "5 or 7",
+        found,
+    }
+}
 
 pub fn draw_and_display_hand<const CARD_NUMBER: usize>(mut rng: ThreadRng) {
-    println!("");
-    let card_hand = Hand::<CARD_NUMBER>::draw(&mut rng);
+    println!();
+    let card_hand = Hand::<CARD_NUMBER>::draw(&mut rng).sorted();
     println!("{card_hand}");
+    println!("{}", HandValue::from(&card_hand));
+    if let Some(best) = BestFive::from_cards(&card_hand.cards) {
+        let names = |cards: &[Card]| {
+            cards
+                .iter()
+                .map(|card| format!(" {card}"))
+                .collect::<String>()
+        };
+        println!(
+            "Best five: [{}]{}",
+            names(best.made_cards()).trim_start(),
+            names(best.kickers())
+        );
+    }
     let hand_stats = HandStats::from(&card_hand);
     println!("{hand_stats}");
     let hand_score = HandScore::from(&hand_stats);
     println!("{hand_score}");
 }
 
+pub fn score_and_display_cards(card_names: &[String]) -> Result<()> {
+    println!();
+    let cards = draw_cards(card_names)?;
+    check_unique(&cards)?;
+    match BestFive::from_cards(&cards) {
+        Some(best) => println!("{best}"),
+        None => println!("{}", HandValue::from_cards(&cards)),
+    }
+    Ok(())
+}
+
 pub fn print_sorted_deck() {
     println!("Sorted Deck:");
 
-    for id in 0..52 {
-        let card = Card { id };
-
-        if card.suit().id == 3 {
+    for card in Card::iter() {
+        if card.suit() == Suit::Spades {
             println!("  {}", card);
         } else {
             print!("  {}  ", card);
@@ -29,38 +88,219 @@ pub fn print_sorted_deck() {
     }
 }
 
-pub fn draw_and_display_hand_wrapper(hands_number: usize, rng: ThreadRng) {
+pub fn draw_and_display_hand_wrapper(hands_number: usize, rng: ThreadRng) -> Result<()> {
     match hands_number {
-        5 => {
-            draw_and_display_hand::<5>(rng);
-        }
-        7 => {
-            draw_and_display_hand::<7>(rng);
-        }
-        _ => {
-            println!("Invalid number. Enter either 5 or 7")
-        }
+        5 => draw_and_display_hand::<5>(rng),
+        7 => draw_and_display_hand::<7>(rng),
+        _ => return Err(invalid_hand_size(hands_number)),
     }
+    Ok(())
 }
 
 pub fn sample_and_display_statistics(
     hands_number: usize,
     sample_number: u32,
     number_of_threads: u32,
-) {
-    println!("");
+    number_of_decks: usize,
+    mode: CountingMode,
+) -> Result<()> {
+    println!();
 
-    match hands_number {
-        5 => {
-            let scores = parallel_sample_aggregate_scores::<5>(sample_number, number_of_threads);
-            println!("{scores}")
+    let scores = match hands_number {
+        5 => parallel_sample_aggregate_scores::<5>(
+            sample_number,
+            number_of_threads,
+            number_of_decks,
+            mode,
+            rand::random(),
+        )?,
+        7 => parallel_sample_aggregate_scores::<7>(
+            sample_number,
+            number_of_threads,
+            number_of_decks,
+            mode,
+            rand::random(),
+        )?,
+        _ => return Err(invalid_hand_size(hands_number)),
+    };
+    println!("{}", scores.display_counted(mode));
+    Ok(())
+}
+
+pub fn simulate_and_display_draw_poker(sample_number: u32, number_of_threads: u32) -> Result<()> {
+    let stand_pat = |_: &Hand<5>| -> Holds { [true; 5] };
+    let strategies: [(&str, &dyn DiscardStrategy); 4] = [
+        ("Stand pat", &stand_pat),
+        ("Keep pairs, draw three", &KeepPairs { keep_kicker: false }),
+        (
+            "Keep pairs and kicker, draw two",
+            &KeepPairs { keep_kicker: true },
+        ),
+        ("Draw to flushes", &DrawToFlush),
+    ];
+    let scores = parallel_simulate_draw(
+        &strategies.map(|(_, strategy)| strategy),
+        sample_number,
+        number_of_threads,
+        rand::random(),
+    )?;
+    for ((name, _), score) in strategies.iter().zip(scores.iter()) {
+        println!();
+        println!("{name}:");
+        println!("{score}");
+    }
+    Ok(())
+}
+
+fn analyze_and_display_video_poker_hand<P: PayTable>(
+    pay_table: &P,
+    hand_names: &[String],
+) -> Result<()> {
+    let hand = Hand {
+        cards: draw_cards(hand_names)?
+            .try_into()
+            .map_err(|cards: Vec<Card>| PokerError::InvalidCardCount {
+                what: "a video poker hand",
+                expected: "5",
+                found: cards.len(),
+            })?,
+    };
+    let mut analysis = analyze_holds(pay_table, &hand)?;
+    println!("{hand}");
+    let best = best_hold(&analysis)?;
+    analysis.sort_by(|a, b| b.expected_payout.total_cmp(&a.expected_payout));
+    println!("Best holds:");
+    for hold in analysis.iter().take(5) {
+        let held = hand
+            .cards
+            .iter()
+            .zip(hold.holds)
+            .filter(|(_, held)| *held)
+            .map(|(card, _)| card.to_string())
+            .collect::<Vec<_>>();
+        let held = if held.is_empty() {
+            String::from("(discard all)")
+        } else {
+            held.join(" ")
+        };
+        let marker = if hold.holds == best.holds { "*" } else { " " };
+        println!("{marker} {:>9.5}  {held}", hold.expected_payout);
+    }
+    Ok(())
+}
+
+pub fn analyze_and_display_video_poker(
+    game: VideoPokerGame,
+    hand_names: &Option<Vec<String>>,
+) -> Result<()> {
+    println!();
+    match (game, hand_names) {
+        (VideoPokerGame::JacksOrBetter, Some(names)) => {
+            analyze_and_display_video_poker_hand(&JacksOrBetter::default(), names)?
+        }
+        (VideoPokerGame::DeucesWild, Some(names)) => {
+            analyze_and_display_video_poker_hand(&DeucesWild::default(), names)?
+        }
+        (VideoPokerGame::JacksOrBetter, None) => {
+            let rtp = return_to_player(&JacksOrBetter::default());
+            println!("Jacks or Better return to player: {:.4}%", 100.0 * rtp);
+        }
+        (VideoPokerGame::DeucesWild, None) => {
+            let rtp = return_to_player(&DeucesWild::default());
+            println!("Deuces Wild return to player: {:.4}%", 100.0 * rtp);
         }
-        7 => {
-            let scores = parallel_sample_aggregate_scores::<7>(sample_number, number_of_threads);
-            println!("{scores}")
+    }
+    Ok(())
+}
+
+/// `opponents` holds two hole cards for each opponent, one after another.
+pub fn find_and_display_outs(
+    hole_cards: &[String],
+    board: &[String],
+    opponents: &[String],
+) -> Result<()> {
+    println!();
+    let players = std::iter::once(hole_cards)
+        .chain(opponents.chunks(2))
+        .map(draw_cards)
+        .collect::<Result<Vec<_>>>()?;
+    let table = HoldemTable {
+        players,
+        board: draw_cards(board)?,
+        dead_cards: vec![],
+    };
+    println!("{}", find_outs(&table)?);
+    Ok(())
+}
+
+pub fn display_street_probabilities(
+    hole_cards: &[String],
+    board: &[String],
+    samples: Option<u32>,
+) -> Result<()> {
+    println!();
+    let hole_cards = draw_cards(hole_cards)?;
+    let board = draw_cards(board)?;
+    let probabilities = match samples {
+        Some(samples) => {
+            sample_street_probabilities(&mut rand::thread_rng(), &hole_cards, &board, samples)?
+        }
+        None => street_probabilities(&hole_cards, &board)?,
+    };
+    println!("{probabilities}");
+    Ok(())
+}
+
+pub fn find_and_display_nuts(board: &[String], count: usize) -> Result<()> {
+    println!();
+    let nuts = find_nuts(&draw_cards(board)?, count)?;
+    for (i, hand) in nuts.iter().enumerate() {
+        println!("{}. {hand}", i + 1);
+    }
+    Ok(())
+}
+
+pub fn compute_and_save_preflop_table(sample_number: u32, file: &str) -> Result<()> {
+    println!();
+    let table = PreflopTable::compute(sample_number)?;
+    table.save(file).map_err(file_error(file))?;
+    println!("Saved preflop equity table to {file}");
+    Ok(())
+}
+
+pub fn compute_and_save_equity_histograms(
+    num_boards: u32,
+    file: &str,
+    num_bins: usize,
+) -> Result<()> {
+    println!();
+    let histograms = starting_hand_histograms(&mut rand::thread_rng(), num_bins, num_boards)?;
+    save_csv(file, &histograms).map_err(file_error(file))?;
+    println!("Saved equity histograms to {file}");
+    Ok(())
+}
+
+pub fn look_up_preflop_equity(file: &str, hand_name: &str, versus: &Option<String>) -> Result<()> {
+    println!();
+    let table = PreflopTable::load(file).map_err(file_error(file))?;
+    let hand = StartingHand::from_name(hand_name)?;
+    match versus {
+        Some(other_name) => {
+            let other = StartingHand::from_name(other_name)?;
+            println!(
+                "{hand} against {other}: {:>7.3}%",
+                100.0 * table.heads_up_equity(hand, other)
+            );
         }
-        _ => {
-            println!("Invalid number. Enter either 5 or 7")
+        None => {
+            println!("{hand} against random hands:");
+            for num_opponents in 1..=MAX_OPPONENTS {
+                let equity = table
+                    .equity_vs_random(hand, num_opponents)
+                    .ok_or(PokerError::OutOfRange(num_opponents as u8))?;
+                println!("  {num_opponents} opponent(s): {:>7.3}%", 100.0 * equity);
+            }
         }
     }
+    Ok(())
 }
@@ -0,0 +1,271 @@
+use crate::card::Card;
+use crate::deck::remaining_cards;
+use crate::error::{PokerError, Result};
+use crate::hand_value::{HandCategory, HandValue};
+use crate::holdem::{HoldemTable, BOARD_SIZE, HOLE_CARDS};
+use std::fmt;
+
+/// What one unseen card would do for the hero if it came next.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct NextCard {
+    pub card: Card,
+    /// Category of the hero's best hand with the card.
+    pub category: HandCategory,
+    /// True if the hand is then the best at the table, or with no known
+    /// opponents, if it moves up a category that the board alone doesn't
+    /// make. A card that only pairs the board improves every hand equally.
+    pub is_out: bool,
+}
+
+/// Every unseen card on the flop or turn, and whether it is an out.
+#[derive(PartialEq, Debug, Clone)]
+pub struct OutsReport {
+    /// Category of the hero's hand before the next card.
+    pub current: HandCategory,
+    /// True if the board is the flop, so that two cards are still to come.
+    pub on_flop: bool,
+    pub next_cards: Vec<NextCard>,
+}
+
+/// Looks at every card that could come next for the hero (the first player),
+/// given the flop or turn. The hole cards of any opponents must all be known;
+/// their cards are removed from the deck along with any dead cards. Fails if
+/// the table is not valid, a player's hole cards are missing, or the board is
+/// not a flop or turn.
+pub fn find_outs(table: &HoldemTable) -> Result<OutsReport> {
+    table.validate()?;
+    if !(3..BOARD_SIZE).contains(&table.board.len()) {
+        return Err(PokerError::InvalidCardCount {
+            what: "the board",
+            expected: "3 or 4",
+            found: table.board.len(),
+        });
+    }
+    if let Some(hole_cards) = table
+        .players
+        .iter()
+        .find(|hole_cards| hole_cards.len() != HOLE_CARDS)
+    {
+        return Err(PokerError::InvalidCardCount {
+            what: "each player",
+            expected: "2",
+            found: hole_cards.len(),
+        });
+    }
+    let known_cards: Vec<Card> = table
+        .players
+        .iter()
+        .flatten()
+        .chain(table.board.iter())
+        .chain(table.dead_cards.iter())
+        .copied()
+        .collect();
+    let value = |hole_cards: &[Card], next: Option<Card>| {
+        let cards: Vec<Card> = hole_cards
+            .iter()
+            .chain(table.board.iter())
+            .chain(next.iter())
+            .copied()
+            .collect();
+        HandValue::from_cards(&cards)
+    };
+    let current = value(&table.players[0], None).category();
+    let next_cards = remaining_cards(&known_cards)
+        .into_iter()
+        .map(|card| {
+            let hero = value(&table.players[0], Some(card));
+            let is_out = if table.players.len() > 1 {
+                table.players[1..]
+                    .iter()
+                    .all(|hole_cards| hero > value(hole_cards, Some(card)))
+            } else {
+                let board = value(&[], Some(card)).category();
+                hero.category() > current && hero.category() > board
+            };
+            NextCard {
+                card,
+                category: hero.category(),
+                is_out,
+            }
+        })
+        .collect();
+    Ok(OutsReport {
+        current,
+        on_flop: table.board.len() == 3,
+        next_cards,
+    })
+}
+
+impl OutsReport {
+    pub fn outs(&self) -> impl Iterator<Item = &NextCard> {
+        self.next_cards.iter().filter(|next| next.is_out)
+    }
+
+    pub fn num_outs(&self) -> usize {
+        self.outs().count()
+    }
+
+    /// Probability that the next card is an out.
+    pub fn next_card_probability(&self) -> f64 {
+        self.num_outs() as f64 / self.next_cards.len() as f64
+    }
+
+    /// Probability of hitting at least one out by the river: on the turn and
+    /// river from the flop, or just the river from the turn. This counts the
+    /// same outs on both streets, and so ignores runner-runner draws.
+    pub fn by_river_probability(&self) -> f64 {
+        if !self.on_flop {
+            return self.next_card_probability();
+        }
+        let unseen = self.next_cards.len() as f64;
+        let misses = unseen - self.num_outs() as f64;
+        1.0 - (misses * (misses - 1.0)) / (unseen * (unseen - 1.0))
+    }
+
+    /// The unseen cards grouped by the category they make, from best to worst.
+    pub fn by_category(&self) -> Vec<(HandCategory, Vec<NextCard>)> {
+        let mut groups: Vec<(HandCategory, Vec<NextCard>)> = Vec::new();
+        let mut next_cards = self.next_cards.clone();
+        next_cards.sort_by_key(|next| std::cmp::Reverse(next.category));
+        for next in next_cards {
+            match groups.last_mut() {
+                Some((category, cards)) if *category == next.category => cards.push(next),
+                _ => groups.push((next.category, vec![next])),
+            }
+        }
+        groups
+    }
+}
+
+/// Lists the cards for each category, with the outs marked by a `*`.
+impl fmt::Display for OutsReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Outs: {} of {} unseen cards (now {})",
+            self.num_outs(),
+            self.next_cards.len(),
+            self.current.name()
+        )?;
+        for (category, cards) in self.by_category() {
+            let names = cards
+                .iter()
+                .map(|next| format!("{}{}", next.card, if next.is_out { "*" } else { "" }))
+                .collect::<Vec<_>>()
+                .join(" ");
+            write!(f, "\n  {:<15} {:>2}  {names}", category.name(), cards.len())?;
+        }
+        write!(
+            f,
+            "\nNext card:    {:>7.3}%\nBy the river: {:>7.3}%",
+            100.0 * self.next_card_probability(),
+            100.0 * self.by_river_probability()
+        )
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use crate::deck::Deck;
+    use crate::hand_value::HandCategory;
+    use crate::holdem::HoldemTable;
+    use crate::outs::find_outs;
+
+    fn table(deck: &Deck, players: &[&[&str]], board: &[&str]) -> HoldemTable {
+        HoldemTable {
+            players: players
+                .iter()
+                .map(|names| deck.draw_cards(names).unwrap())
+                .collect(),
+            board: deck.draw_cards(board).unwrap(),
+            dead_cards: vec![],
+        }
+    }
+
+    /// The nut flush draw, with no known opponents: every card that pairs a
+    /// hole card also counts as an out, as it moves up from high card.
+    #[test]
+    fn flush_draw() {
+        let deck = Deck::new();
+        let report = find_outs(&table(&deck, &[&["A♥", "K♥"]], &["2♥", "7♥", "9♣"])).unwrap();
+        assert_eq!(report.current, HandCategory::HighCard);
+        assert_eq!(report.next_cards.len(), 47);
+        let flush_cards = report
+            .outs()
+            .filter(|next| next.category == HandCategory::Flush)
+            .count();
+        assert_eq!(flush_cards, 9);
+        assert_eq!(report.by_category()[0].0, HandCategory::Flush);
+        assert_eq!(report.by_category()[0].1.len(), 9);
+        // Three cards each to pair the ace and the king.
+        assert_eq!(report.num_outs(), 9 + 2 * 3);
+        assert!((report.next_card_probability() - 15.0 / 47.0).abs() < 1e-12);
+    }
+
+    /// A turn that pairs the board gives the hero a pair, but so it does for
+    /// everyone else, so it is not an out.
+    #[test]
+    fn board_pairing_card_is_not_an_out() {
+        let deck = Deck::new();
+        let report = find_outs(&table(&deck, &[&["A♥", "K♥"]], &["2♥", "7♥", "9♣"])).unwrap();
+        let nine = deck.draw_card("9♦").unwrap();
+        let next = report
+            .next_cards
+            .iter()
+            .find(|next| next.card == nine)
+            .unwrap();
+        assert_eq!(next.category, HandCategory::Pair);
+        assert!(!next.is_out);
+
+        // With a pocket pair, the same card makes two pair that uses both
+        // hole cards.
+        let report = find_outs(&table(&deck, &[&["5♠", "5♦"]], &["2♥", "7♥", "9♣"])).unwrap();
+        let next = report
+            .next_cards
+            .iter()
+            .find(|next| next.card == nine)
+            .unwrap();
+        assert_eq!(next.category, HandCategory::TwoPair);
+        assert!(next.is_out);
+    }
+
+    /// Against a set, only the flush cards that don't pair the board win.
+    #[test]
+    fn outs_against_opponent() {
+        let deck = Deck::new();
+        let report = find_outs(&table(
+            &deck,
+            &[&["A♥", "K♥"], &["9♦", "9♠"]],
+            &["2♥", "7♥", "9♣", "3♠"],
+        ))
+        .unwrap();
+        assert_eq!(report.next_cards.len(), 44);
+        // Nine hearts remain, but 9♥ makes quads and 3♥ a full house.
+        assert_eq!(report.num_outs(), 7);
+        assert!((report.by_river_probability() - 7.0 / 44.0).abs() < 1e-12);
+        let text = report.to_string();
+        assert!(text.starts_with("Outs: 7 of 44 unseen cards (now High card)"));
+        assert!(text.contains("\n  Flush            9  3♥ 4♥* 5♥*"));
+        assert!(text.contains("\n  High card       21  4♣"));
+    }
+
+    #[test]
+    fn invalid_boards() {
+        let deck = Deck::new();
+        assert!(find_outs(&table(&deck, &[&["A♥", "K♥"]], &[])).is_err());
+        assert!(find_outs(&table(
+            &deck,
+            &[&["A♥", "K♥"]],
+            &["2♥", "7♥", "9♣", "3♠", "4♠"]
+        ))
+        .is_err());
+        assert!(find_outs(&table(
+            &deck,
+            &[&["A♥", "K♥"], &["2♠"]],
+            &["2♥", "7♥", "9♣"]
+        ))
+        .is_err());
+    }
+}
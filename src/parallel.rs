@@ -0,0 +1,128 @@
+//! Iteration that runs on the rayon thread pool when the `parallel` feature
+//! is on, and sequentially otherwise. Modules import the prelude instead of
+//! `rayon::prelude`, so the same `into_par_iter()` calls build either way.
+
+use crate::error::{PokerError, Result};
+use prelude::*;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::sync::Mutex;
+
+#[cfg(feature = "parallel")]
+pub(crate) mod prelude {
+    pub(crate) use rayon::prelude::*;
+}
+
+#[cfg(not(feature = "parallel"))]
+pub(crate) mod prelude {
+    /// Stand-in for rayon's trait of the same name, which hands back the
+    /// ordinary sequential iterator.
+    pub(crate) trait IntoParallelIterator: IntoIterator + Sized {
+        fn into_par_iter(self) -> Self::IntoIter {
+            self.into_iter()
+        }
+    }
+
+    impl<I: IntoIterator> IntoParallelIterator for I {}
+}
+
+/// Splits `num_samples` into one batch per thread, with any remainder in an
+/// extra batch at the end.
+fn split_samples(num_samples: u32, num_threads: u32) -> Result<Vec<u32>> {
+    if num_threads == 0 {
+        return Err(PokerError::Zero("threads"));
+    }
+    let mut sample_sizes = vec![num_samples / num_threads; num_threads as usize];
+    let remainder = num_samples % num_threads;
+    if remainder > 0 {
+        sample_sizes.push(remainder);
+    }
+    Ok(sample_sizes)
+}
+
+/// Random number generator for one batch of a seeded run. The seed and the
+/// batch index together make up the key, so every batch of every seed draws
+/// from its own stream.
+pub(crate) fn batch_rng(seed: u64, batch: usize) -> StdRng {
+    let mut key = [0; 32];
+    key[..8].copy_from_slice(&seed.to_le_bytes());
+    key[8..16].copy_from_slice(&(batch as u64).to_le_bytes());
+    StdRng::from_seed(key)
+}
+
+/// Runs `num_samples` samples in batches, as split by `split_samples`, and
+/// merges the results of every batch into `empty`. Each batch is run with
+/// `run(batch_index, batch_size)`, on its own thread when the `parallel`
+/// feature is on.
+pub(crate) fn parallel_batches<T, F, M>(
+    num_samples: u32,
+    num_threads: u32,
+    empty: T,
+    run: F,
+    merge: M,
+) -> Result<T>
+where
+    T: Send,
+    F: Fn(usize, u32) -> Result<T> + Send + Sync,
+    M: Fn(&mut T, &T) + Send + Sync,
+{
+    let total = Mutex::new(empty);
+    split_samples(num_samples, num_threads)?
+        .into_par_iter()
+        .enumerate()
+        .try_for_each(|(batch, batch_size)| -> Result<()> {
+            let result = run(batch, batch_size)?;
+            merge(&mut total.lock().unwrap(), &result);
+            Ok(())
+        })?;
+    Ok(total.into_inner().unwrap())
+}
+
+//////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use crate::error::PokerError;
+    use crate::parallel::parallel_batches;
+    use crate::parallel::split_samples;
+
+    #[test]
+    fn split_samples_into_batches() {
+        assert_eq!(split_samples(1001, 4).unwrap(), vec![250, 250, 250, 250, 1]);
+        assert_eq!(split_samples(8, 4).unwrap(), vec![2, 2, 2, 2]);
+        assert!(matches!(
+            split_samples(10, 0),
+            Err(PokerError::Zero("threads"))
+        ));
+    }
+
+    #[test]
+    fn merge_every_batch() {
+        let mut batches = parallel_batches(
+            1001,
+            4,
+            vec![],
+            |batch, batch_size| Ok(vec![(batch, batch_size)]),
+            |total: &mut Vec<(usize, u32)>, result| total.extend_from_slice(result),
+        )
+        .unwrap();
+        batches.sort();
+        assert_eq!(
+            batches,
+            vec![(0, 250), (1, 250), (2, 250), (3, 250), (4, 1)]
+        );
+
+        // An error in any batch is returned.
+        let failed = parallel_batches(
+            10,
+            2,
+            0,
+            |batch, batch_size| match batch {
+                0 => Ok(batch_size),
+                _ => Err(PokerError::NotEnoughCards),
+            },
+            |total, result| *total += result,
+        );
+        assert!(matches!(failed, Err(PokerError::NotEnoughCards)));
+    }
+}
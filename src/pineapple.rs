@@ -0,0 +1,243 @@
+use crate::card::Card;
+use crate::deck::remaining_cards;
+use crate::error::{PokerError, Result};
+use crate::hand::check_unique;
+use crate::hand_value::{ace_high_index, HandValue};
+use crate::holdem::{award_pot, simulate_holdem, HoldemResults, HoldemTable, BOARD_SIZE};
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+/// Pineapple deals three hole cards to each player, who then discards one and
+/// plays the rest of the hand as Texas hold'em.
+pub const PINEAPPLE_HOLE_CARDS: usize = 3;
+const FLOP_SIZE: usize = 3;
+
+#[derive(PartialEq, Eq, Debug, Copy, Clone, Hash)]
+pub enum PineappleVariant {
+    /// The discard is made before the flop.
+    Pineapple,
+    /// The discard is made after seeing the flop.
+    CrazyPineapple,
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct DiscardAnalysis {
+    pub kept: [Card; 2],
+    pub discard: Card,
+    /// Share of the pot won with the kept cards.
+    pub equity: f64,
+}
+
+/// The three ways to keep two of the three hole cards, with the discard.
+fn discard_options(hole_cards: &[Card; PINEAPPLE_HOLE_CARDS]) -> [([Card; 2], Card); 3] {
+    [
+        ([hole_cards[1], hole_cards[2]], hole_cards[0]),
+        ([hole_cards[0], hole_cards[2]], hole_cards[1]),
+        ([hole_cards[0], hole_cards[1]], hole_cards[2]),
+    ]
+}
+
+/// Equity of each possible discard, found by simulating the rest of the hand
+/// `num_samples` times with the two kept cards against `num_opponents` random
+/// hold'em hands. The discarded card is dead. `board` holds the community
+/// cards dealt so far, which is empty for Pineapple and the flop for Crazy
+/// Pineapple. Fails if the cards do not make a valid table, or there are no
+/// samples.
+///
+/// The opponents' kept cards are treated as random, even though they also
+/// chose the best two of three.
+pub fn analyze_discards<R: Rng>(
+    rng: &mut R,
+    hole_cards: &[Card; PINEAPPLE_HOLE_CARDS],
+    board: &[Card],
+    num_opponents: usize,
+    num_samples: u32,
+) -> Result<Vec<DiscardAnalysis>> {
+    if num_samples == 0 {
+        return Err(PokerError::Zero("samples"));
+    }
+    discard_options(hole_cards)
+        .iter()
+        .map(|(kept, discard)| {
+            let mut players = vec![vec![]; num_opponents + 1];
+            players[0] = kept.to_vec();
+            let table = HoldemTable {
+                players,
+                board: board.to_vec(),
+                dead_cards: vec![*discard],
+            };
+            let results = simulate_holdem(rng, &table, num_samples)?;
+            Ok(DiscardAnalysis {
+                kept: *kept,
+                discard: *discard,
+                equity: results.equity()[0],
+            })
+        })
+        .collect()
+}
+
+/// The discard with the highest equity.
+pub fn best_discard(analyses: &[DiscardAnalysis]) -> Option<&DiscardAnalysis> {
+    analyses.iter().max_by(|a, b| a.equity.total_cmp(&b.equity))
+}
+
+/// A rough ranking of two hole cards before the flop: pairs first, then by
+/// high card, suitedness, and low card.
+fn preflop_strength(cards: &[Card; 2]) -> (bool, usize, bool, usize) {
+    let ranks = [
+        ace_high_index(cards[0].rank()),
+        ace_high_index(cards[1].rank()),
+    ];
+    (
+        ranks[0] == ranks[1],
+        ranks[0].max(ranks[1]),
+        cards[0].suit() == cards[1].suit(),
+        ranks[0].min(ranks[1]),
+    )
+}
+
+/// The two cards a simulated player keeps: the strongest two before the flop
+/// in Pineapple, or those that make the best hand with the flop in Crazy
+/// Pineapple.
+fn keep_two(
+    variant: PineappleVariant,
+    hole_cards: &[Card; PINEAPPLE_HOLE_CARDS],
+    flop: &[Card],
+) -> [Card; 2] {
+    let options = discard_options(hole_cards);
+    let (kept, _) = match variant {
+        PineappleVariant::Pineapple => options
+            .iter()
+            .max_by_key(|(kept, _)| preflop_strength(kept)),
+        PineappleVariant::CrazyPineapple => options.iter().max_by_key(|(kept, _)| {
+            let cards: Vec<Card> = kept.iter().chain(flop.iter()).copied().collect();
+            (HandValue::from_cards(&cards), preflop_strength(kept))
+        }),
+    }
+    .unwrap();
+    *kept
+}
+
+/// Deals out Pineapple hands `num_samples` times, where every player keeps two
+/// of their three cards with a simple rule: the strongest starting hand for
+/// Pineapple, or the best hand on the flop for Crazy Pineapple. Each entry of
+/// `players` holds the known hole cards of a player, from none up to three.
+/// Fails if the cards are not unique or there are too many.
+pub fn simulate_pineapple<R: Rng>(
+    rng: &mut R,
+    variant: PineappleVariant,
+    players: &[Vec<Card>],
+    num_samples: u32,
+) -> Result<HoldemResults> {
+    if players.is_empty() {
+        return Err(PokerError::NoPlayers);
+    }
+    if let Some(hole_cards) = players
+        .iter()
+        .find(|hole_cards| hole_cards.len() > PINEAPPLE_HOLE_CARDS)
+    {
+        return Err(PokerError::InvalidCardCount {
+            what: "each player",
+            expected: "at most 3",
+            found: hole_cards.len(),
+        });
+    }
+    let known_cards: Vec<Card> = players.iter().flatten().copied().collect();
+    check_unique(&known_cards)?;
+    let num_unknown_cards = BOARD_SIZE + PINEAPPLE_HOLE_CARDS * players.len() - known_cards.len();
+    if num_unknown_cards > Card::NUM_CARDS - known_cards.len() {
+        return Err(PokerError::NotEnoughCards);
+    }
+
+    let mut stub = remaining_cards(&known_cards);
+    let mut results = HoldemResults::new(players.len());
+    results.num_samples = num_samples as u64;
+    let mut cards = Vec::with_capacity(BOARD_SIZE + 2);
+    let mut values = Vec::with_capacity(players.len());
+    for _ in 0..num_samples {
+        let (dealt, _) = stub.partial_shuffle(rng, num_unknown_cards);
+        let (board, mut dealt) = dealt.split_at(BOARD_SIZE);
+        values.clear();
+        for known in players {
+            let (missing, rest) = dealt.split_at(PINEAPPLE_HOLE_CARDS - known.len());
+            dealt = rest;
+            let mut hole_cards = known.iter().chain(missing.iter());
+            let hole_cards = array_init::array_init(|_| *hole_cards.next().unwrap());
+            cards.clear();
+            cards.extend_from_slice(board);
+            cards.extend_from_slice(&keep_two(variant, &hole_cards, &board[..FLOP_SIZE]));
+            values.push(HandValue::from_cards(&cards));
+        }
+        award_pot(&values, &mut results.wins);
+    }
+    Ok(results)
+}
+
+//////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use crate::deck::Deck;
+    use crate::error::PokerError;
+    use crate::pineapple::analyze_discards;
+    use crate::pineapple::best_discard;
+    use crate::pineapple::keep_two;
+    use crate::pineapple::simulate_pineapple;
+    use crate::pineapple::PineappleVariant;
+    use rand::SeedableRng;
+
+    #[test]
+    fn simple_keep_rules() {
+        let deck = Deck::new();
+        let hole_cards = deck.draw_hand(&["7♣", "A♠", "7♦"]).unwrap().cards;
+        let flop = deck.draw_cards(&["A♦", "K♥", "2♠"]).unwrap();
+        let preflop = keep_two(PineappleVariant::Pineapple, &hole_cards, &flop);
+        assert_eq!(preflop, [hole_cards[0], hole_cards[2]]);
+        let on_flop = keep_two(PineappleVariant::CrazyPineapple, &hole_cards, &flop);
+        assert_eq!(on_flop, [hole_cards[0], hole_cards[1]]);
+    }
+
+    /// With a set on the flop, discarding the other card is clearly best.
+    #[test]
+    fn best_discard_on_flop() {
+        let deck = Deck::new();
+        let mut rng = rand::rngs::StdRng::seed_from_u64(333);
+        let hole_cards = deck.draw_hand(&["9♣", "9♦", "4♥"]).unwrap().cards;
+        let flop = deck.draw_cards(&["9♠", "K♥", "2♣"]).unwrap();
+        let analyses = analyze_discards(&mut rng, &hole_cards, &flop, 2, 2000).unwrap();
+        assert_eq!(analyses.len(), 3);
+        let best = best_discard(&analyses).unwrap();
+        assert_eq!(best.discard, hole_cards[2]);
+        assert!(best.equity > 0.8);
+        let duplicate = deck.draw_cards(&["9♣", "K♥", "2♣"]).unwrap();
+        assert!(analyze_discards(&mut rng, &hole_cards, &duplicate, 2, 10).is_err());
+        assert!(matches!(
+            analyze_discards(&mut rng, &hole_cards, &flop, 2, 0),
+            Err(PokerError::Zero("samples"))
+        ));
+    }
+
+    /// Equities sum to one in both variants, and three suited broadway cards
+    /// stay well ahead of two random opponents.
+    #[test]
+    fn variants() {
+        let deck = Deck::new();
+        let mut rng = rand::rngs::StdRng::seed_from_u64(8080);
+        let hero = deck.draw_cards(&["A♠", "K♠", "Q♠"]).unwrap();
+        for variant in [
+            PineappleVariant::Pineapple,
+            PineappleVariant::CrazyPineapple,
+        ] {
+            let results =
+                simulate_pineapple(&mut rng, variant, &[hero.clone(), vec![], vec![]], 4000)
+                    .unwrap();
+            let total: f64 = results.equity().iter().sum();
+            assert!((total - 1.0).abs() < 1e-9);
+            assert!(results.equity()[0] > 0.4);
+        }
+        let too_many = deck.draw_cards(&["A♠", "K♠", "Q♠", "J♠"]).unwrap();
+        assert!(
+            simulate_pineapple(&mut rng, PineappleVariant::Pineapple, &[too_many], 10).is_err()
+        );
+    }
+}
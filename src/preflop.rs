@@ -0,0 +1,415 @@
+use crate::card::{Card, Rank, Suit};
+use crate::error::{PokerError, Result};
+use crate::hand_value::ace_high_index;
+use crate::holdem::{simulate_holdem, HoldemTable};
+use crate::parallel::prelude::*;
+use rand::Rng;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+/// There are 13 pairs, 78 suited and 78 offsuit starting hands.
+pub const NUM_STARTING_HANDS: usize = Rank::NUM_RANKS * Rank::NUM_RANKS;
+pub const MAX_OPPONENTS: usize = 9;
+
+/// Version of the file format written by [`PreflopTable::write`]. Files with a
+/// different version are rejected when read.
+pub const PREFLOP_TABLE_VERSION: u32 = 1;
+const PREFLOP_TABLE_HEADER: &str = "poker-stats preflop equity table";
+
+/// One of the 169 strategically different hold'em starting hands, which only
+/// depend on the two ranks and whether the cards are suited.
+#[derive(PartialEq, Eq, Debug, Copy, Clone, Hash)]
+pub struct StartingHand {
+    pub high: Rank,
+    pub low: Rank,
+    pub suited: bool,
+}
+
+impl StartingHand {
+    pub fn from_cards(cards: &[Card; 2]) -> StartingHand {
+        let (high, low) = if ace_high_index(cards[0].rank()) >= ace_high_index(cards[1].rank()) {
+            (cards[0].rank(), cards[1].rank())
+        } else {
+            (cards[1].rank(), cards[0].rank())
+        };
+        StartingHand {
+            high,
+            low,
+            suited: cards[0].suit() == cards[1].suit(),
+        }
+    }
+
+    /// Position in the usual 13 by 13 grid, read row by row: aces first, with
+    /// pairs on the diagonal, suited hands above it and offsuit hands below.
+    pub fn index(&self) -> usize {
+        let row = |rank: Rank| Rank::NUM_RANKS - 1 - ace_high_index(rank);
+        let (high, low) = (row(self.high), row(self.low));
+        if self.suited {
+            high * Rank::NUM_RANKS + low
+        } else {
+            low * Rank::NUM_RANKS + high
+        }
+    }
+
+    pub fn from_index(index: usize) -> StartingHand {
+        let rank = |row: usize| Rank::ALL[(Rank::NUM_RANKS - row) % Rank::NUM_RANKS];
+        let (row, column) = (index / Rank::NUM_RANKS, index % Rank::NUM_RANKS);
+        StartingHand {
+            high: rank(row.min(column)),
+            low: rank(row.max(column)),
+            suited: row < column,
+        }
+    }
+
+    /// Parses names like "AA", "AKs" or "T9o".
+    pub fn from_name(name: &str) -> Result<StartingHand> {
+        let invalid = || PokerError::InvalidStartingHand(name.to_string());
+        let rank = |c: char| {
+            Rank::iter()
+                .find(|rank| rank.to_string() == c.to_string())
+                .ok_or_else(invalid)
+        };
+        let chars: Vec<char> = name.chars().collect();
+        let hand = match chars[..] {
+            [high, low] => StartingHand {
+                high: rank(high)?,
+                low: rank(low)?,
+                suited: false,
+            },
+            [high, low, 's'] | [high, low, 'o'] => StartingHand {
+                high: rank(high)?,
+                low: rank(low)?,
+                suited: chars[2] == 's',
+            },
+            _ => return Err(invalid()),
+        };
+        let is_pair = hand.high == hand.low;
+        if is_pair != (chars.len() == 2) || ace_high_index(hand.high) < ace_high_index(hand.low) {
+            return Err(invalid());
+        }
+        Ok(hand)
+    }
+
+    /// Every way to deal the hand: 6 for a pair, 4 suited and 12 offsuit.
+    pub fn combos(&self) -> Vec<[Card; 2]> {
+        let mut combos = Vec::new();
+        for high_suit in Suit::iter() {
+            for low_suit in Suit::iter() {
+                let keep = if self.high == self.low {
+                    high_suit < low_suit
+                } else {
+                    (high_suit == low_suit) == self.suited
+                };
+                if keep {
+                    combos.push([
+                        Card::new(&self.high, &high_suit),
+                        Card::new(&self.low, &low_suit),
+                    ]);
+                }
+            }
+        }
+        combos
+    }
+}
+
+/// Examples: AA, AKs, T9o
+impl fmt::Display for StartingHand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let suffix = match (self.high == self.low, self.suited) {
+            (true, _) => "",
+            (false, true) => "s",
+            (false, false) => "o",
+        };
+        write!(f, "{}{}{}", self.high, self.low, suffix)
+    }
+}
+
+/// Equity of `hand` all-in before the flop against `num_opponents` random
+/// hands. Every combo of a starting hand has the same equity against random
+/// hands, so one of them is enough. Fails if there are too many opponents to
+/// deal to.
+pub fn equity_vs_random<R: Rng>(
+    rng: &mut R,
+    hand: StartingHand,
+    num_opponents: usize,
+    num_samples: u32,
+) -> Result<f64> {
+    let mut players = vec![vec![]; num_opponents + 1];
+    players[0] = hand.combos()[0].to_vec();
+    let table = HoldemTable {
+        players,
+        ..Default::default()
+    };
+    Ok(simulate_holdem(rng, &table, num_samples)?.equity()[0])
+}
+
+/// Equity of `hand` all-in before the flop against `other`. Here the suits
+/// do matter, so the samples are split evenly between every pair of combos
+/// that can be dealt together, with at least one sample for each pair. Fails
+/// if there are no samples.
+pub fn heads_up_equity<R: Rng>(
+    rng: &mut R,
+    hand: StartingHand,
+    other: StartingHand,
+    num_samples: u32,
+) -> Result<f64> {
+    if num_samples == 0 {
+        return Err(PokerError::Zero("samples"));
+    }
+    let other_combos = other.combos();
+    let matchups: Vec<HoldemTable> = hand
+        .combos()
+        .iter()
+        .flat_map(|combo| {
+            other_combos
+                .iter()
+                .filter(|other_combo| other_combo.iter().all(|card| !combo.contains(card)))
+                .map(|other_combo| HoldemTable {
+                    players: vec![combo.to_vec(), other_combo.to_vec()],
+                    ..Default::default()
+                })
+        })
+        .collect();
+    let num_matchups = matchups.len() as u32;
+    let mut total = 0.0;
+    for (index, table) in matchups.iter().enumerate() {
+        // The first few matchups take one extra sample each for the remainder.
+        let extra = (index as u32) < num_samples % num_matchups;
+        let num_table_samples = (num_samples / num_matchups + extra as u32).max(1);
+        total += simulate_holdem(rng, table, num_table_samples)?.equity()[0];
+    }
+    // Every pair of combos is equally likely to be dealt.
+    Ok(total / num_matchups as f64)
+}
+
+/// Preflop all-in equities of every starting hand, against one to nine random
+/// opponents and heads-up against every other starting hand. Computing the
+/// table takes a while, so it can be saved to a file and loaded for lookups.
+#[derive(PartialEq, Debug, Clone)]
+pub struct PreflopTable {
+    /// Number of samples behind each entry.
+    pub num_samples: u32,
+    vs_random: Vec<[f64; MAX_OPPONENTS]>,
+    heads_up: Vec<f64>,
+}
+
+impl PreflopTable {
+    /// Samples every entry `num_samples` times, one starting hand per thread.
+    /// Heads-up equities are only sampled once for each pair of hands, since
+    /// the two equities add up to one.
+    pub fn compute(num_samples: u32) -> Result<PreflopTable> {
+        let rows: Vec<([f64; MAX_OPPONENTS], Vec<f64>)> = (0..NUM_STARTING_HANDS)
+            .into_par_iter()
+            .map(|index| {
+                let rng = &mut rand::thread_rng();
+                let hand = StartingHand::from_index(index);
+                let vs_random = (1..=MAX_OPPONENTS)
+                    .map(|opponents| equity_vs_random(rng, hand, opponents, num_samples))
+                    .collect::<Result<Vec<f64>>>()?;
+                let heads_up = (0..index)
+                    .map(|other| {
+                        heads_up_equity(rng, hand, StartingHand::from_index(other), num_samples)
+                    })
+                    .collect::<Result<Vec<f64>>>()?;
+                Ok((array_init::array_init(|i| vs_random[i]), heads_up))
+            })
+            .collect::<Result<_>>()?;
+
+        let mut heads_up = vec![0.5; NUM_STARTING_HANDS * NUM_STARTING_HANDS];
+        for (index, (_, row)) in rows.iter().enumerate() {
+            for (other, equity) in row.iter().enumerate() {
+                heads_up[index * NUM_STARTING_HANDS + other] = *equity;
+                heads_up[other * NUM_STARTING_HANDS + index] = 1.0 - equity;
+            }
+        }
+        Ok(PreflopTable {
+            num_samples,
+            vs_random: rows.into_iter().map(|(vs_random, _)| vs_random).collect(),
+            heads_up,
+        })
+    }
+
+    /// Returns `None` unless there are one to nine opponents.
+    pub fn equity_vs_random(&self, hand: StartingHand, num_opponents: usize) -> Option<f64> {
+        if !(1..=MAX_OPPONENTS).contains(&num_opponents) {
+            return None;
+        }
+        Some(self.vs_random[hand.index()][num_opponents - 1])
+    }
+
+    pub fn heads_up_equity(&self, hand: StartingHand, other: StartingHand) -> f64 {
+        self.heads_up[hand.index() * NUM_STARTING_HANDS + other.index()]
+    }
+
+    /// Writes the table as text: a header line with the format version, the
+    /// number of samples, then one line per starting hand with its equities
+    /// against one to nine opponents followed by its 169 heads-up equities.
+    pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writeln!(writer, "{PREFLOP_TABLE_HEADER} v{PREFLOP_TABLE_VERSION}")?;
+        writeln!(writer, "samples {}", self.num_samples)?;
+        for index in 0..NUM_STARTING_HANDS {
+            write!(writer, "{}", StartingHand::from_index(index))?;
+            let heads_up =
+                &self.heads_up[index * NUM_STARTING_HANDS..(index + 1) * NUM_STARTING_HANDS];
+            for equity in self.vs_random[index].iter().chain(heads_up.iter()) {
+                write!(writer, " {equity:.6}")?;
+            }
+            writeln!(writer)?;
+        }
+        Ok(())
+    }
+
+    /// Reads a table written by [`PreflopTable::write`].
+    pub fn read<R: BufRead>(reader: R) -> io::Result<PreflopTable> {
+        let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+        let mut lines = reader.lines();
+        let mut next_line = || {
+            lines
+                .next()
+                .unwrap_or_else(|| Err(invalid(String::from("unexpected end of file"))))
+        };
+
+        let header = next_line()?;
+        let expected = format!("{PREFLOP_TABLE_HEADER} v{PREFLOP_TABLE_VERSION}");
+        if header != expected {
+            return Err(invalid(format!(
+                "expected \"{expected}\", found \"{header}\""
+            )));
+        }
+        let num_samples = next_line()?
+            .strip_prefix("samples ")
+            .and_then(|samples| samples.parse().ok())
+            .ok_or_else(|| invalid(String::from("missing number of samples")))?;
+
+        let mut vs_random = Vec::with_capacity(NUM_STARTING_HANDS);
+        let mut heads_up = Vec::with_capacity(NUM_STARTING_HANDS * NUM_STARTING_HANDS);
+        for index in 0..NUM_STARTING_HANDS {
+            let line = next_line()?;
+            let mut fields = line.split_whitespace();
+            let name = StartingHand::from_index(index).to_string();
+            if fields.next() != Some(name.as_str()) {
+                return Err(invalid(format!("expected a line for {name}")));
+            }
+            let equities: Vec<f64> = fields
+                .map(|field| field.parse())
+                .collect::<std::result::Result<_, _>>()
+                .map_err(|_| invalid(format!("invalid equity for {name}")))?;
+            if equities.len() != MAX_OPPONENTS + NUM_STARTING_HANDS {
+                return Err(invalid(format!("wrong number of equities for {name}")));
+            }
+            vs_random.push(array_init::array_init(|i| equities[i]));
+            heads_up.extend_from_slice(&equities[MAX_OPPONENTS..]);
+        }
+        Ok(PreflopTable {
+            num_samples,
+            vs_random,
+            heads_up,
+        })
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write(&mut writer)?;
+        writer.flush()
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<PreflopTable> {
+        PreflopTable::read(BufReader::new(File::open(path)?))
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use crate::error::PokerError;
+    use crate::preflop::equity_vs_random;
+    use crate::preflop::heads_up_equity;
+    use crate::preflop::PreflopTable;
+    use crate::preflop::StartingHand;
+    use crate::preflop::NUM_STARTING_HANDS;
+    use rand::SeedableRng;
+
+    #[test]
+    fn starting_hands() {
+        let mut num_combos = 0;
+        for index in 0..NUM_STARTING_HANDS {
+            let hand = StartingHand::from_index(index);
+            assert_eq!(hand.index(), index);
+            assert_eq!(StartingHand::from_name(&hand.to_string()).ok(), Some(hand));
+            for combo in hand.combos() {
+                assert_eq!(StartingHand::from_cards(&combo), hand);
+                num_combos += 1;
+            }
+        }
+        assert_eq!(num_combos, 1326);
+        assert_eq!(StartingHand::from_index(0).to_string(), "AA");
+        assert_eq!(StartingHand::from_index(1).to_string(), "AKs");
+        assert_eq!(StartingHand::from_index(13).to_string(), "AKo");
+        assert_eq!(StartingHand::from_index(168).to_string(), "22");
+        assert!(StartingHand::from_name("KAs").is_err());
+        assert!(StartingHand::from_name("AAs").is_err());
+        assert!(StartingHand::from_name("AK").is_err());
+    }
+
+    /// Well known values: aces win 85.2% heads-up against a random hand, and
+    /// 81.9% against kings.
+    #[test]
+    fn sampled_equities() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(169);
+        let aces = StartingHand::from_name("AA").unwrap();
+        let kings = StartingHand::from_name("KK").unwrap();
+        let equity = equity_vs_random(&mut rng, aces, 1, 5000).unwrap();
+        assert!((equity - 0.852).abs() < 0.02);
+        let equity = heads_up_equity(&mut rng, aces, kings, 5000).unwrap();
+        assert!((equity - 0.819).abs() < 0.02);
+        assert!(matches!(
+            heads_up_equity(&mut rng, aces, kings, 0),
+            Err(PokerError::Zero("samples"))
+        ));
+    }
+
+    /// With fewer samples than the 36 pairs of combos, each pair is still
+    /// dealt once, so the equity is an average over all of them instead of a
+    /// win or loss in the first pair.
+    #[test]
+    fn fewer_samples_than_matchups() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(36);
+        let aces = StartingHand::from_name("AA").unwrap();
+        let kings = StartingHand::from_name("KK").unwrap();
+        let equity = heads_up_equity(&mut rng, aces, kings, 1).unwrap();
+        assert!(equity > 0.5 && equity < 1.0, "{equity}");
+        // Ties split the pot, so each pair adds a multiple of half of 1/36.
+        assert_eq!((equity * 72.0).fract(), 0.0);
+        assert!(equity_vs_random(&mut rng, aces, 25, 10).is_err());
+    }
+
+    #[test]
+    fn save_and_load() {
+        let table = PreflopTable::compute(2).unwrap();
+        let aces = StartingHand::from_name("AA").unwrap();
+        let sevens = StartingHand::from_name("72o").unwrap();
+        let equity = table.heads_up_equity(aces, sevens);
+        assert!((table.heads_up_equity(sevens, aces) - (1.0 - equity)).abs() < 1e-12);
+        assert_eq!(table.heads_up_equity(aces, aces), 0.5);
+        assert!(table.equity_vs_random(aces, 0).is_none());
+        assert!(table.equity_vs_random(aces, 9).is_some());
+
+        let mut buffer = Vec::new();
+        table.write(&mut buffer).unwrap();
+        let loaded = PreflopTable::read(buffer.as_slice()).unwrap();
+        assert_eq!(loaded.num_samples, 2);
+        assert_eq!(
+            loaded.equity_vs_random(aces, 3),
+            table.equity_vs_random(aces, 3)
+        );
+
+        let text = String::from_utf8(buffer).unwrap();
+        let old_version = text.replacen(" v1", " v0", 1);
+        assert!(PreflopTable::read(old_version.as_bytes()).is_err());
+        let truncated = &text[..text.len() / 2];
+        assert!(PreflopTable::read(truncated.as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,315 @@
+use crate::aggregate_score::AggregateScore;
+use crate::card::Card;
+use crate::deck::remaining_cards;
+use crate::error::{PokerError, Result};
+use crate::hand::{check_unique, Hand};
+use crate::hand_score::HandScore;
+use crate::hand_value::HandValue;
+use crate::parallel::{batch_rng, parallel_batches};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::fmt;
+
+/// Seven card stud deals two down cards, four up cards (third through sixth
+/// street), and then a final down card on seventh street.
+pub const STUD_HAND_SIZE: usize = 7;
+pub const MAX_DOWN_CARDS: usize = 3;
+pub const MAX_UP_CARDS: usize = 4;
+
+/// The cards that are known for one player who is still in the hand. For
+/// opponents this is usually just the up cards, while the hero also knows
+/// their down cards. Any missing cards are dealt at random.
+#[derive(Default, PartialEq, Debug, Clone)]
+pub struct StudPlayer {
+    pub down_cards: Vec<Card>,
+    pub up_cards: Vec<Card>,
+}
+
+/// Everything known about a seven card stud hand in progress: the players
+/// that are still in the hand, and the cards of players that have folded.
+#[derive(Default, PartialEq, Debug, Clone)]
+pub struct StudTable {
+    pub players: Vec<StudPlayer>,
+    pub dead_cards: Vec<Card>,
+}
+
+/// Simulation results for a single player.
+#[derive(Default, PartialEq, Debug)]
+pub struct StudPlayerResult {
+    /// Number of showdowns won, where ties split the pot evenly.
+    pub wins: f64,
+    /// Category of the final seven card hand in each showdown.
+    pub scores: AggregateScore,
+}
+
+#[derive(Default, PartialEq, Debug)]
+pub struct StudResults {
+    pub num_samples: u32,
+    pub players: Vec<StudPlayerResult>,
+}
+
+impl StudTable {
+    fn known_cards(&self) -> Vec<Card> {
+        self.players
+            .iter()
+            .flat_map(|player| player.down_cards.iter().chain(player.up_cards.iter()))
+            .chain(self.dead_cards.iter())
+            .copied()
+            .collect()
+    }
+
+    /// Number of cards that must still be dealt to finish the hand.
+    fn num_unknown_cards(&self) -> usize {
+        self.players
+            .iter()
+            .map(|player| STUD_HAND_SIZE - player.down_cards.len() - player.up_cards.len())
+            .sum()
+    }
+
+    /// Checks that the table could occur in a real game: every card is dealt
+    /// at most once, no player has too many cards, and there are enough cards
+    /// left in the deck to finish the hand.
+    pub fn validate(&self) -> Result<()> {
+        if self.players.is_empty() {
+            return Err(PokerError::NoPlayers);
+        }
+        for player in &self.players {
+            if player.down_cards.len() > MAX_DOWN_CARDS {
+                return Err(PokerError::InvalidCardCount {
+                    what: "each player's down cards",
+                    expected: "at most 3",
+                    found: player.down_cards.len(),
+                });
+            }
+            if player.up_cards.len() > MAX_UP_CARDS {
+                return Err(PokerError::InvalidCardCount {
+                    what: "each player's up cards",
+                    expected: "at most 4",
+                    found: player.up_cards.len(),
+                });
+            }
+        }
+        let known_cards = self.known_cards();
+        check_unique(&known_cards)?;
+        if self.num_unknown_cards() > Card::NUM_CARDS - known_cards.len() {
+            return Err(PokerError::NotEnoughCards);
+        }
+        Ok(())
+    }
+
+    /// Returns true if the table could occur in a real game, see `validate`.
+    pub fn is_valid(&self) -> bool {
+        self.validate().is_ok()
+    }
+}
+
+impl StudResults {
+    fn new(num_players: usize) -> StudResults {
+        StudResults {
+            num_samples: 0,
+            players: (0..num_players).map(|_| Default::default()).collect(),
+        }
+    }
+
+    pub fn insert(&mut self, other: &StudResults) {
+        self.num_samples += other.num_samples;
+        for (player, other_player) in self.players.iter_mut().zip(other.players.iter()) {
+            player.wins += other_player.wins;
+            player.scores.insert(&other_player.scores);
+        }
+    }
+
+    /// Share of the pot that each player wins on average.
+    pub fn equity(&self) -> Vec<f64> {
+        self.players
+            .iter()
+            .map(|player| player.wins / (self.num_samples as f64))
+            .collect()
+    }
+}
+
+impl fmt::Display for StudResults {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "StudResults: {} samples", self.num_samples)?;
+        for (index, (player, equity)) in self.players.iter().zip(self.equity()).enumerate() {
+            write!(
+                f,
+                "\nPlayer {index}: equity {:>7.3}%\n{}",
+                100.0 * equity,
+                player.scores
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Deals out the rest of the hand `num_samples` times, and records who wins
+/// each showdown along with the category of every player's final hand.
+/// Fails if the table is not valid.
+pub fn simulate_stud<R: Rng>(
+    rng: &mut R,
+    table: &StudTable,
+    num_samples: u32,
+) -> Result<StudResults> {
+    table.validate()?;
+    let mut stub = remaining_cards(&table.known_cards());
+    let num_unknown_cards = table.num_unknown_cards();
+    let mut results = StudResults::new(table.players.len());
+    results.num_samples = num_samples;
+    let mut values = vec![None; table.players.len()];
+
+    for _ in 0..num_samples {
+        let (dealt, _) = stub.partial_shuffle(rng, num_unknown_cards);
+        let mut dealt = dealt.iter();
+        for (index, player) in table.players.iter().enumerate() {
+            let mut known = player.down_cards.iter().chain(player.up_cards.iter());
+            let hand = Hand::<STUD_HAND_SIZE> {
+                cards: array_init::array_init(|_| *known.next().or_else(|| dealt.next()).unwrap()),
+            };
+            results.players[index]
+                .scores
+                .insert(&HandScore::from(&hand));
+            values[index] = Some(HandValue::from(&hand));
+        }
+        let best = values.iter().max().unwrap();
+        let num_winners = values.iter().filter(|value| *value == best).count();
+        for (index, value) in values.iter().enumerate() {
+            if value == best {
+                results.players[index].wins += 1.0 / (num_winners as f64);
+            }
+        }
+    }
+    Ok(results)
+}
+
+/// Multi-threaded version of `simulate_stud`, with one batch of samples per
+/// thread. The batches are seeded from `seed`, so a run can be repeated.
+pub fn parallel_simulate_stud(
+    table: &StudTable,
+    num_samples: u32,
+    num_threads: u32,
+    seed: u64,
+) -> Result<StudResults> {
+    table.validate()?;
+    parallel_batches(
+        num_samples,
+        num_threads,
+        StudResults::new(table.players.len()),
+        |batch, batch_size| simulate_stud(&mut batch_rng(seed, batch), table, batch_size),
+        StudResults::insert,
+    )
+}
+
+//////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use crate::deck::Deck;
+    use crate::error::PokerError;
+    use crate::hand_value::HandCategory;
+    use crate::stud::parallel_simulate_stud;
+    use crate::stud::simulate_stud;
+    use crate::stud::StudPlayer;
+    use crate::stud::StudTable;
+    use rand::SeedableRng;
+
+    fn player(deck: &Deck, down: &[&str], up: &[&str]) -> StudPlayer {
+        StudPlayer {
+            down_cards: deck.draw_cards(down).unwrap(),
+            up_cards: deck.draw_cards(up).unwrap(),
+        }
+    }
+
+    #[test]
+    fn invalid_tables() {
+        let deck = Deck::new();
+        let mut rng = rand::rngs::StdRng::seed_from_u64(4512);
+        // The same card can't be dealt twice.
+        let table = StudTable {
+            players: vec![
+                player(&deck, &["A♠", "A♥"], &["K♦"]),
+                player(&deck, &[], &["K♦"]),
+            ],
+            dead_cards: vec![],
+        };
+        assert!(matches!(
+            simulate_stud(&mut rng, &table, 10),
+            Err(PokerError::DuplicateCards)
+        ));
+        // Eight players would need 56 cards.
+        let table = StudTable {
+            players: vec![StudPlayer::default(); 8],
+            dead_cards: vec![],
+        };
+        assert!(matches!(
+            simulate_stud(&mut rng, &table, 10),
+            Err(PokerError::NotEnoughCards)
+        ));
+    }
+
+    #[test]
+    fn finished_hand_is_deterministic() {
+        let deck = Deck::new();
+        let mut rng = rand::rngs::StdRng::seed_from_u64(23);
+        let table = StudTable {
+            players: vec![
+                player(&deck, &["A♠", "A♥", "2♣"], &["K♦", "K♠", "7♣", "3♦"]),
+                player(&deck, &["Q♠", "Q♥", "2♦"], &["Q♦", "4♠", "7♦", "9♦"]),
+            ],
+            dead_cards: vec![],
+        };
+        let results = simulate_stud(&mut rng, &table, 20).unwrap();
+        assert_eq!(results.equity(), vec![0.0, 1.0]);
+        assert_eq!(results.players[1].scores[HandCategory::ThreeOfAKind], 20);
+        assert_eq!(results.players[0].scores[HandCategory::TwoPair], 20);
+    }
+
+    /// Rolled up aces against a four flush on board, with the other cards of
+    /// that suit folded. Equities always sum to one.
+    #[test]
+    fn dead_cards_reduce_outs() {
+        let deck = Deck::new();
+        let mut rng = rand::rngs::StdRng::seed_from_u64(771);
+        let hero = player(&deck, &["A♠", "A♥"], &["A♦", "2♣", "3♣"]);
+        let villain = player(&deck, &[], &["9♥", "T♥", "J♥", "Q♥"]);
+        let live = StudTable {
+            players: vec![hero.clone(), villain.clone()],
+            dead_cards: vec![],
+        };
+        let dead = StudTable {
+            players: vec![hero, villain],
+            dead_cards: deck.draw_cards(&["2♥", "3♥", "4♥", "5♥", "6♥"]).unwrap(),
+        };
+        let live_results = simulate_stud(&mut rng, &live, 4000).unwrap();
+        let dead_results = simulate_stud(&mut rng, &dead, 4000).unwrap();
+        for results in [&live_results, &dead_results] {
+            let total: f64 = results.equity().iter().sum();
+            assert!((total - 1.0).abs() < 1e-9);
+        }
+        assert!(dead_results.equity()[0] > live_results.equity()[0]);
+    }
+
+    #[test]
+    fn parallel_simulation() {
+        let deck = Deck::new();
+        let table = StudTable {
+            players: vec![
+                player(&deck, &["A♠", "A♥"], &["A♦"]),
+                StudPlayer::default(),
+                StudPlayer::default(),
+            ],
+            dead_cards: vec![],
+        };
+        let results = parallel_simulate_stud(&table, 1001, 4, 17).unwrap();
+        assert_eq!(results.num_samples, 1001);
+        assert_eq!(
+            results,
+            parallel_simulate_stud(&table, 1001, 4, 17).unwrap()
+        );
+        assert_eq!(results.players[0].scores[HandCategory::HighCard], 1001);
+        assert_eq!(results.players[0].scores[HandCategory::ThreeOfAKind], 1001);
+        let text = results.to_string();
+        assert!(text.starts_with("StudResults: 1001 samples\nPlayer 0: equity  87.812%\n"));
+        assert!(text.contains("\n  three_of_a_kind   1001 (100.000%)\n"));
+    }
+}
@@ -0,0 +1,308 @@
+use crate::card::{Card, Rank, Suit};
+use crate::hand::Hand;
+use itertools::Itertools;
+
+/// A relabeling of the four suits: suit `s` becomes `permutation[s]`.
+pub type SuitPermutation = [usize; Suit::NUM_SUITS];
+
+/// All 24 ways to relabel the suits.
+pub fn suit_permutations() -> Vec<SuitPermutation> {
+    (0..Suit::NUM_SUITS)
+        .permutations(Suit::NUM_SUITS)
+        .map(|permutation| array_init::array_init(|i| permutation[i]))
+        .collect()
+}
+
+pub fn permute_card(card: Card, permutation: &SuitPermutation) -> Card {
+    Card::new(&card.rank(), &Suit::ALL[permutation[card.suit().index()]])
+}
+
+/// Relabels the suits of every card, and sorts each group by id.
+fn permute_groups(groups: &[&[Card]], permutation: &SuitPermutation) -> Vec<Vec<Card>> {
+    groups
+        .iter()
+        .map(|group| {
+            let mut cards: Vec<Card> = group
+                .iter()
+                .map(|card| permute_card(*card, permutation))
+                .collect();
+            cards.sort_by_key(|card| card.id());
+            cards
+        })
+        .collect()
+}
+
+/// Cards with the suits relabeled into a canonical form. Two sets of cards
+/// that only differ by a relabeling of the suits have the same canonical
+/// form, and play out the same way in any game where all suits are equal.
+#[derive(PartialEq, Eq, Debug, Clone, Hash)]
+pub struct CanonicalCards {
+    /// The canonical form of each group of cards, sorted by id.
+    pub groups: Vec<Vec<Card>>,
+    /// Number of different sets of cards with this canonical form.
+    pub multiplicity: u32,
+}
+
+/// Finds the canonical form of some groups of cards, for example the hole
+/// cards and the board in hold'em. The order of the cards within a group does
+/// not matter, but the groups are kept apart: a heart in the hole is not the
+/// same as a heart on the board. The canonical form is the relabeling that
+/// sorts first, comparing the card ids one group at a time.
+pub fn canonicalize(groups: &[&[Card]]) -> CanonicalCards {
+    let mut best: Option<Vec<Vec<Card>>> = None;
+    let mut num_best = 0;
+    for permutation in suit_permutations() {
+        let permuted = permute_groups(groups, &permutation);
+        let ids = |groups: &Vec<Vec<Card>>| -> Vec<Vec<u8>> {
+            groups
+                .iter()
+                .map(|cards| cards.iter().map(|card| card.id()).collect())
+                .collect()
+        };
+        match &best {
+            Some(current) if ids(&permuted) > ids(current) => {}
+            Some(current) if ids(&permuted) == ids(current) => num_best += 1,
+            _ => {
+                best = Some(permuted);
+                num_best = 1;
+            }
+        }
+    }
+    // The relabelings that give the canonical form are the ones that map the
+    // cards onto themselves, so each different set of cards is reached by
+    // the same number of them.
+    CanonicalCards {
+        groups: best.unwrap(),
+        multiplicity: (suit_permutations().len() / num_best) as u32,
+    }
+}
+
+/// Canonical form of a single hand, and the number of hands that share it.
+pub fn canonical_hand<const N: usize>(hand: &Hand<N>) -> (Hand<N>, u32) {
+    let canonical = canonicalize(&[&hand.cards]);
+    (
+        Hand {
+            cards: array_init::array_init(|i| canonical.groups[0][i]),
+        },
+        canonical.multiplicity,
+    )
+}
+
+/// Every N card hand that can be dealt after the `known` cards, with hands
+/// that only differ by a relabeling of the suits combined into one. Only
+/// relabelings that leave the known cards unchanged are used, so the hands
+/// still play the same way with them. Each hand comes with the number of
+/// hands it stands for, and these weights add up to the number of hands.
+///
+/// With no known cards, this gives the 1,755 strategically different flops
+/// out of 22,100.
+pub fn canonical_hands<const N: usize>(known: &[Card]) -> Vec<(Hand<N>, u64)> {
+    let mut hands = Vec::new();
+    for_each_canonical_hand::<N, _>(known, |hand, weight| hands.push((*hand, weight)));
+    hands
+}
+
+/// Visits the hands of [`canonical_hands`] without collecting them. The hands
+/// are built directly from the ranks dealt in each suit, so `func` is called
+/// once per canonical hand and no other hand is looked at.
+pub fn for_each_canonical_hand<const N: usize, F>(known: &[Card], mut func: F)
+where
+    F: FnMut(&Hand<N>, u64),
+{
+    // The relabelings that leave the known cards unchanged are the ones that
+    // only swap suits holding the same known ranks.
+    let mut known_ranks = [0; Suit::NUM_SUITS];
+    for card in known {
+        known_ranks[card.suit().index()] |= 1 << card.rank().index();
+    }
+    let mut rank_sets_by_size = vec![vec![]; Rank::NUM_RANKS + 1];
+    for ranks in 0..1u16 << Rank::NUM_RANKS {
+        rank_sets_by_size[ranks.count_ones() as usize].push(ranks);
+    }
+    let mut dealt_ranks = [0; Suit::NUM_SUITS];
+    choose_suit_ranks(
+        &known_ranks,
+        &rank_sets_by_size,
+        &mut dealt_ranks,
+        0,
+        N,
+        &mut func,
+    );
+}
+
+/// Recursive helper for [`for_each_canonical_hand`]: choose the ranks dealt
+/// in `suit` and the suits after it, `remaining` cards in all. A suit that
+/// can be swapped with an earlier one is dealt no more than it, comparing the
+/// number of cards and then the ranks, so each hand is reached through
+/// exactly one of its relabelings.
+fn choose_suit_ranks<const N: usize, F>(
+    known_ranks: &[u16; Suit::NUM_SUITS],
+    rank_sets_by_size: &[Vec<u16>],
+    dealt_ranks: &mut [u16; Suit::NUM_SUITS],
+    suit: usize,
+    remaining: usize,
+    func: &mut F,
+) where
+    F: FnMut(&Hand<N>, u64),
+{
+    if suit == Suit::NUM_SUITS {
+        visit_suit_ranks(known_ranks, dealt_ranks, func);
+        return;
+    }
+    let order = |ranks: u16| (ranks.count_ones(), ranks);
+    let bound = (0..suit)
+        .rev()
+        .find(|earlier| known_ranks[*earlier] == known_ranks[suit])
+        .map(|earlier| order(dealt_ranks[earlier]));
+    // The last suit takes all the cards that are left.
+    let min_size = if suit + 1 == Suit::NUM_SUITS {
+        remaining
+    } else {
+        0
+    };
+    for size in min_size..=remaining.min(Rank::NUM_RANKS) {
+        for ranks in &rank_sets_by_size[size] {
+            if ranks & known_ranks[suit] != 0 || bound.is_some_and(|bound| order(*ranks) > bound) {
+                continue;
+            }
+            dealt_ranks[suit] = *ranks;
+            choose_suit_ranks(
+                known_ranks,
+                rank_sets_by_size,
+                dealt_ranks,
+                suit + 1,
+                remaining - size,
+                func,
+            );
+        }
+    }
+}
+
+/// Builds the hand with the chosen ranks in each suit, and counts the
+/// different hands it stands for. Swappable suits are dealt in order, so the
+/// relabelings of a group of k of them with the same ranks give k! times the
+/// same hand.
+fn visit_suit_ranks<const N: usize, F>(
+    known_ranks: &[u16; Suit::NUM_SUITS],
+    dealt_ranks: &[u16; Suit::NUM_SUITS],
+    func: &mut F,
+) where
+    F: FnMut(&Hand<N>, u64),
+{
+    let mut cards = Vec::with_capacity(N);
+    let mut weight = 1;
+    for (suit, ranks) in dealt_ranks.iter().enumerate() {
+        for rank in Rank::ALL {
+            if ranks & 1 << rank.index() != 0 {
+                cards.push(Card::new(&rank, &Suit::ALL[suit]));
+            }
+        }
+        let swappable = (0..suit).filter(|earlier| known_ranks[*earlier] == known_ranks[suit]);
+        let same = swappable
+            .clone()
+            .filter(|earlier| dealt_ranks[*earlier] == *ranks)
+            .count() as u64;
+        weight = weight * (swappable.count() as u64 + 1) / (same + 1);
+    }
+    cards.sort_by_key(|card| card.id());
+    func(
+        &Hand {
+            cards: array_init::array_init(|i| cards[i]),
+        },
+        weight,
+    );
+}
+
+//////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use crate::aggregate_score::enumerate_aggregate_scores_with_known;
+    use crate::aggregate_score::AggregateScore;
+    use crate::aggregate_score::CountingMode;
+    use crate::deck::Deck;
+    use crate::hand::for_each_hand_in_shoe;
+    use crate::hand_score::HandScore;
+    use crate::hand_stats::HandStats;
+    use crate::suit_isomorphism::canonical_hand;
+    use crate::suit_isomorphism::canonical_hands;
+    use crate::suit_isomorphism::canonicalize;
+    use crate::suit_isomorphism::for_each_canonical_hand;
+    use std::collections::HashMap;
+
+    #[test]
+    fn canonical_forms() {
+        let deck = Deck::new();
+        let (hearts, multiplicity) = canonical_hand(&deck.draw_hand(&["A♥", "K♥"]).unwrap());
+        let (spades, _) = canonical_hand(&deck.draw_hand(&["K♠", "A♠"]).unwrap());
+        assert_eq!(hearts, spades);
+        assert_eq!(multiplicity, 4);
+        let (_, multiplicity) = canonical_hand(&deck.draw_hand(&["A♥", "K♠"]).unwrap());
+        assert_eq!(multiplicity, 12);
+        let (_, multiplicity) = canonical_hand(&deck.draw_hand(&["A♥", "A♠"]).unwrap());
+        assert_eq!(multiplicity, 6);
+
+        // A flush draw is not the same as a backdoor draw with a suited board.
+        let hole = deck.draw_cards(&["A♥", "K♥"]).unwrap();
+        let draw = deck.draw_cards(&["2♥", "7♥", "9♣"]).unwrap();
+        let backdoor = deck.draw_cards(&["2♣", "7♣", "9♥"]).unwrap();
+        let same_draw = deck.draw_cards(&["2♦", "7♦", "9♠"]).unwrap();
+        let hole_spades = deck.draw_cards(&["A♦", "K♦"]).unwrap();
+        assert_ne!(
+            canonicalize(&[&hole, &draw]),
+            canonicalize(&[&hole, &backdoor])
+        );
+        assert_eq!(
+            canonicalize(&[&hole, &draw]),
+            canonicalize(&[&hole_spades, &same_draw])
+        );
+    }
+
+    /// The 1,755 canonical flops are evaluated once each, instead of once
+    /// for each of the 22,100 flops, and each stands for all the flops with
+    /// the same canonical form.
+    #[test]
+    fn distinct_flops() {
+        let mut evaluations = 0;
+        let mut total_weight = 0;
+        let mut weights = HashMap::new();
+        for_each_canonical_hand::<3, _>(&[], |flop, weight| {
+            evaluations += 1;
+            total_weight += weight;
+            let (canonical, multiplicity) = canonical_hand(flop);
+            assert_eq!(weight, multiplicity as u64);
+            assert!(weights.insert(canonical, weight).is_none());
+        });
+        assert_eq!(evaluations, 1755);
+        assert_eq!(total_weight, 22100);
+
+        let mut counts = HashMap::new();
+        for_each_hand_in_shoe::<3, _>(1, |flop, _| {
+            *counts.entry(canonical_hand(flop).0).or_insert(0) += 1;
+        });
+        assert_eq!(counts, weights);
+        assert_eq!(canonical_hands::<3>(&[]).len(), 1755);
+    }
+
+    /// Scoring the weighted canonical flops gives the same counts as scoring
+    /// every flop, with far fewer hands scored.
+    #[test]
+    fn weighted_enumeration() {
+        let known = Deck::new().draw_cards(&["A♥", "K♥"]).unwrap();
+        let flops = canonical_hands::<3>(&known);
+        assert!(flops.len() < 19600 / 3);
+        let mut scores = AggregateScore::default();
+        for (flop, weight) in &flops {
+            let mut hand_stats = HandStats::from_cards(&known);
+            for card in flop.cards {
+                hand_stats.insert(card);
+            }
+            scores.insert_weighted(&HandScore::from(&hand_stats), *weight);
+        }
+        assert_eq!(
+            scores,
+            enumerate_aggregate_scores_with_known::<5>(&known, 1, CountingMode::Cumulative)
+                .unwrap()
+        );
+    }
+}
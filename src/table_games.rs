@@ -0,0 +1,458 @@
+use crate::card::{Card, Rank, Suit};
+use crate::card_subsets::{binomial, CardSubsets};
+use crate::hand::{for_each_hand_in_shoe, Hand};
+use crate::hand_stats::HandStats;
+use crate::hand_value::{ace_high_index, HandCategory, HandValue};
+use std::convert::From;
+
+/// Three card hands rank straights above flushes, because with only three
+/// cards a straight is the harder hand to make.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Copy, Clone, Hash)]
+pub enum ThreeCardCategory {
+    HighCard,
+    Pair,
+    Flush,
+    Straight,
+    ThreeOfAKind,
+    StraightFlush,
+}
+
+/// Comparable value of a three card hand: the category, followed by the
+/// ace-high ranks that decide ties, most significant first.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Copy, Clone, Hash)]
+pub struct ThreeCardValue {
+    pub category: ThreeCardCategory,
+    ranks: [usize; 3],
+}
+
+impl From<&HandStats> for ThreeCardValue {
+    fn from(hand_stats: &HandStats) -> ThreeCardValue {
+        // Ace-high ranks from highest to lowest, with one entry per card.
+        let mut ranks = [0; 3];
+        let mut num_ranks = 0;
+        for rank in Rank::iter().skip(1).chain(std::iter::once(Rank::Ace)).rev() {
+            for _ in 0..hand_stats.rank_count[rank.index()] {
+                if num_ranks < ranks.len() {
+                    ranks[num_ranks] = ace_high_index(rank);
+                    num_ranks += 1;
+                }
+            }
+        }
+
+        let flush = hand_stats.suit_count.contains(&3);
+        let distinct = ranks[0] != ranks[1] && ranks[1] != ranks[2];
+        let ace = Rank::NUM_RANKS - 1;
+        // A-2-3 is the lowest straight, so treat it as three-high.
+        let wheel = ranks == [ace, 1, 0];
+        let straight = distinct && (ranks[0] - ranks[2] == 2 || wheel);
+        let ranks = if wheel { [1, 0, 0] } else { ranks };
+
+        let category = match (straight, flush) {
+            (true, true) => ThreeCardCategory::StraightFlush,
+            _ if ranks[0] == ranks[2] => ThreeCardCategory::ThreeOfAKind,
+            (true, false) => ThreeCardCategory::Straight,
+            (false, true) => ThreeCardCategory::Flush,
+            _ if !distinct => ThreeCardCategory::Pair,
+            _ => ThreeCardCategory::HighCard,
+        };
+        let ranks = match category {
+            // Pair first, then the kicker.
+            ThreeCardCategory::Pair if ranks[1] == ranks[2] => [ranks[1], ranks[0], 0],
+            ThreeCardCategory::Pair => [ranks[0], ranks[2], 0],
+            _ => ranks,
+        };
+        ThreeCardValue { category, ranks }
+    }
+}
+
+impl From<&Hand<3>> for ThreeCardValue {
+    fn from(hand: &Hand<3>) -> ThreeCardValue {
+        ThreeCardValue::from(&HandStats::from(hand))
+    }
+}
+
+/// How the dealer's hand compares with one player hand, counted over every
+/// dealer hand that can be dealt from the rest of the deck.
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub struct ShowdownCounts {
+    /// The dealer does not qualify, whatever the player holds.
+    pub not_qualified: i64,
+    /// The dealer qualifies, and the player has the better hand.
+    pub player_wins: i64,
+    /// The dealer qualifies, and the hands tie.
+    pub ties: i64,
+    /// The dealer qualifies, and has the better hand.
+    pub dealer_wins: i64,
+}
+
+impl ShowdownCounts {
+    pub fn total(&self) -> i64 {
+        self.not_qualified + self.player_wins + self.ties + self.dealer_wins
+    }
+}
+
+/// Calls `func` for every N card player hand, with its value and how it fares
+/// against all of the N card dealer hands that can be dealt from the remaining
+/// cards. The dealer qualifies with a hand of at least `qualifier`.
+///
+/// Rather than comparing every pair of hands, the hands are visited from worst
+/// to best while counting how many hands with a lower value contain each set
+/// of cards. The number of lower dealer hands that avoid the player's cards
+/// then follows by inclusion-exclusion, see [`CardSubsets`].
+pub fn for_each_showdown<const N: usize, V, FV, F>(value: FV, qualifier: V, mut func: F)
+where
+    V: Ord + Copy,
+    FV: Fn(&Hand<N>) -> V,
+    F: FnMut(&Hand<N>, V, ShowdownCounts),
+{
+    // Each card is a single byte, so the millions of five card hands fit.
+    let mut hands: Vec<(V, [Card; N])> = Vec::with_capacity(binomial(Card::NUM_CARDS, N));
+    for_each_hand_in_shoe::<N, _>(1, |hand, _| {
+        hands.push((value(hand), hand.cards));
+    });
+    hands.sort_by_key(|(value, _)| *value);
+    let to_hand = |cards: &[Card; N]| Hand::<N> { cards: *cards };
+
+    let mut not_qualified = CardSubsets::new(N);
+    for (_, cards) in hands.iter().take_while(|(value, _)| *value < qualifier) {
+        not_qualified.add_to_subsets(&to_hand(cards).cards, 1);
+    }
+    let num_dealer_hands = binomial(Card::NUM_CARDS - N, N) as i64;
+    let mut lower = CardSubsets::new(N);
+    let mut lower_counts = Vec::new();
+    for group in hands.chunk_by(|a, b| a.0 == b.0) {
+        let group_value = group[0].0;
+        lower_counts.clear();
+        for (_, cards) in group {
+            lower_counts.push(lower.total_avoiding_others(&to_hand(cards).cards, 0));
+        }
+        for (_, cards) in group {
+            lower.add_to_subsets(&to_hand(cards).cards, 1);
+        }
+        for ((_, cards), num_lower) in group.iter().zip(lower_counts.iter()) {
+            let hand = to_hand(cards);
+            let num_not_qualified = not_qualified.total_avoiding_others(&hand.cards, 0);
+            let counts = if group_value < qualifier {
+                ShowdownCounts {
+                    not_qualified: num_not_qualified,
+                    dealer_wins: num_dealer_hands - num_not_qualified,
+                    ..Default::default()
+                }
+            } else {
+                let num_lower_or_equal = lower.total_avoiding_others(&hand.cards, 0);
+                ShowdownCounts {
+                    not_qualified: num_not_qualified,
+                    player_wins: num_lower - num_not_qualified,
+                    ties: num_lower_or_equal - num_lower,
+                    dealer_wins: num_dealer_hands - num_lower_or_equal,
+                }
+            };
+            func(&hand, group_value, counts);
+        }
+    }
+}
+
+/// House edge of a casino game, per unit of the initial bet, when the player
+/// makes the best decision for every hand.
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub struct HouseEdge {
+    /// Expected loss per unit of the initial bet.
+    pub house_edge: f64,
+    /// Fraction of hands where the player should continue rather than fold.
+    pub play_rate: f64,
+}
+
+/// Pay tables for Three Card Poker. The ante bonus is paid on the ante when
+/// the player plays, whatever the dealer holds, and the pair plus side bet
+/// only depends on the player's hand. The default is the common "1-4-6-30-40"
+/// pair plus table.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ThreeCardPokerPayTable {
+    pub ante_bonus_straight_flush: u32,
+    pub ante_bonus_three_of_a_kind: u32,
+    pub ante_bonus_straight: u32,
+    pub pair_plus_straight_flush: u32,
+    pub pair_plus_three_of_a_kind: u32,
+    pub pair_plus_straight: u32,
+    pub pair_plus_flush: u32,
+    pub pair_plus_pair: u32,
+}
+
+impl Default for ThreeCardPokerPayTable {
+    fn default() -> Self {
+        ThreeCardPokerPayTable {
+            ante_bonus_straight_flush: 5,
+            ante_bonus_three_of_a_kind: 4,
+            ante_bonus_straight: 1,
+            pair_plus_straight_flush: 40,
+            pair_plus_three_of_a_kind: 30,
+            pair_plus_straight: 6,
+            pair_plus_flush: 4,
+            pair_plus_pair: 1,
+        }
+    }
+}
+
+impl ThreeCardPokerPayTable {
+    fn ante_bonus(&self, category: ThreeCardCategory) -> u32 {
+        match category {
+            ThreeCardCategory::StraightFlush => self.ante_bonus_straight_flush,
+            ThreeCardCategory::ThreeOfAKind => self.ante_bonus_three_of_a_kind,
+            ThreeCardCategory::Straight => self.ante_bonus_straight,
+            _ => 0,
+        }
+    }
+
+    /// Winnings per unit bet on pair plus, or `None` if the bet loses.
+    fn pair_plus(&self, category: ThreeCardCategory) -> Option<u32> {
+        match category {
+            ThreeCardCategory::StraightFlush => Some(self.pair_plus_straight_flush),
+            ThreeCardCategory::ThreeOfAKind => Some(self.pair_plus_three_of_a_kind),
+            ThreeCardCategory::Straight => Some(self.pair_plus_straight),
+            ThreeCardCategory::Flush => Some(self.pair_plus_flush),
+            ThreeCardCategory::Pair => Some(self.pair_plus_pair),
+            ThreeCardCategory::HighCard => None,
+        }
+    }
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub struct ThreeCardPokerHouseEdge {
+    /// The ante and play bets, per unit of ante.
+    pub ante_play: HouseEdge,
+    /// The pair plus side bet, per unit bet.
+    pub pair_plus: f64,
+}
+
+/// Exact house edge of Three Card Poker, found by playing every player hand
+/// against every dealer hand. The player antes one unit, and after seeing
+/// their cards either folds or plays by betting one more. The dealer
+/// qualifies with queen-high or better; if not, the ante wins and the play
+/// bet pushes.
+pub fn three_card_poker_house_edge(pay_table: &ThreeCardPokerPayTable) -> ThreeCardPokerHouseEdge {
+    let queen_high = ThreeCardValue {
+        category: ThreeCardCategory::HighCard,
+        ranks: [
+            ace_high_index(Rank::Queen),
+            ace_high_index(Rank::Three),
+            ace_high_index(Rank::Two),
+        ],
+    };
+    let mut total_return = 0.0;
+    let mut num_played = 0;
+    let mut total_pair_plus = 0.0;
+    let mut num_hands = 0;
+    for_each_showdown::<3, _, _, _>(
+        |hand| ThreeCardValue::from(hand),
+        queen_high,
+        |_, value, counts| {
+            let total = counts.total() as f64;
+            let play = pay_table.ante_bonus(value.category) as f64
+                + (counts.not_qualified as f64 + 2.0 * counts.player_wins as f64
+                    - 2.0 * counts.dealer_wins as f64)
+                    / total;
+            if play > -1.0 {
+                num_played += 1;
+                total_return += play;
+            } else {
+                total_return -= 1.0;
+            }
+            total_pair_plus += pay_table
+                .pair_plus(value.category)
+                .map_or(-1.0, |payout| payout as f64);
+            num_hands += 1;
+        },
+    );
+    ThreeCardPokerHouseEdge {
+        ante_play: HouseEdge {
+            house_edge: -total_return / num_hands as f64,
+            play_rate: num_played as f64 / num_hands as f64,
+        },
+        pair_plus: -total_pair_plus / num_hands as f64,
+    }
+}
+
+/// Pay table for the raise in Caribbean Stud, in units won per unit raised
+/// when the dealer qualifies and the player wins. The default is the most
+/// common table.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CaribbeanStudPayTable {
+    pub royal_flush: u32,
+    pub straight_flush: u32,
+    pub four_of_a_kind: u32,
+    pub full_house: u32,
+    pub flush: u32,
+    pub straight: u32,
+    pub three_of_a_kind: u32,
+    pub two_pair: u32,
+    pub pair_or_less: u32,
+}
+
+impl Default for CaribbeanStudPayTable {
+    fn default() -> Self {
+        CaribbeanStudPayTable {
+            royal_flush: 100,
+            straight_flush: 50,
+            four_of_a_kind: 20,
+            full_house: 7,
+            flush: 5,
+            straight: 4,
+            three_of_a_kind: 3,
+            two_pair: 2,
+            pair_or_less: 1,
+        }
+    }
+}
+
+impl CaribbeanStudPayTable {
+    fn raise_payout(&self, value: &HandValue) -> u32 {
+        match value.category() {
+            HandCategory::StraightFlush if value.tiebreak_ranks()[0] == Rank::Ace => {
+                self.royal_flush
+            }
+            HandCategory::StraightFlush => self.straight_flush,
+            HandCategory::FourOfAKind => self.four_of_a_kind,
+            HandCategory::FullHouse => self.full_house,
+            HandCategory::Flush => self.flush,
+            HandCategory::Straight => self.straight,
+            HandCategory::ThreeOfAKind => self.three_of_a_kind,
+            HandCategory::TwoPair => self.two_pair,
+            _ => self.pair_or_less,
+        }
+    }
+}
+
+/// Exact house edge of Caribbean Stud, found by playing every five card
+/// player hand against every dealer hand. The player antes one unit, and
+/// either folds or raises two more. The dealer qualifies with ace-king or
+/// better; if not, the ante wins and the raise pushes.
+///
+/// The player's decision here only depends on their own five cards. In the
+/// casino the player also sees one of the dealer's cards, which lowers the
+/// house edge very slightly.
+pub fn caribbean_stud_house_edge(pay_table: &CaribbeanStudPayTable) -> HouseEdge {
+    let card = |rank, suit| Card::new(&rank, &suit);
+    let ace_king = HandValue::from_cards(&[
+        card(Rank::Ace, Suit::Clubs),
+        card(Rank::King, Suit::Diamonds),
+        card(Rank::Four, Suit::Clubs),
+        card(Rank::Three, Suit::Clubs),
+        card(Rank::Two, Suit::Clubs),
+    ]);
+    let mut total_return = 0.0;
+    let mut num_played = 0;
+    let mut num_hands = 0;
+    for_each_showdown::<5, _, _, _>(
+        |hand| HandValue::from(hand),
+        ace_king,
+        |_, value, counts| {
+            let raise = (counts.not_qualified as f64
+                + (1.0 + 2.0 * pay_table.raise_payout(&value) as f64) * counts.player_wins as f64
+                - 3.0 * counts.dealer_wins as f64)
+                / counts.total() as f64;
+            if raise > -1.0 {
+                num_played += 1;
+                total_return += raise;
+            } else {
+                total_return -= 1.0;
+            }
+            num_hands += 1;
+        },
+    );
+    HouseEdge {
+        house_edge: -total_return / num_hands as f64,
+        play_rate: num_played as f64 / num_hands as f64,
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use crate::deck::Deck;
+    use crate::table_games::caribbean_stud_house_edge;
+    use crate::table_games::for_each_showdown;
+    use crate::table_games::three_card_poker_house_edge;
+    use crate::table_games::CaribbeanStudPayTable;
+    use crate::table_games::ThreeCardCategory;
+    use crate::table_games::ThreeCardPokerPayTable;
+    use crate::table_games::ThreeCardValue;
+
+    fn three_card_value(deck: &Deck, names: &[&str; 3]) -> ThreeCardValue {
+        ThreeCardValue::from(&deck.draw_hand(names).unwrap())
+    }
+
+    #[test]
+    fn three_card_categories() {
+        let deck = Deck::new();
+        let cases = [
+            (["Q♠", "6♦", "4♣"], ThreeCardCategory::HighCard),
+            (["Q♠", "6♦", "Q♣"], ThreeCardCategory::Pair),
+            (["Q♠", "6♠", "4♠"], ThreeCardCategory::Flush),
+            (["A♠", "2♦", "3♣"], ThreeCardCategory::Straight),
+            (["Q♠", "K♦", "A♣"], ThreeCardCategory::Straight),
+            (["K♠", "A♦", "2♣"], ThreeCardCategory::HighCard),
+            (["7♠", "7♦", "7♣"], ThreeCardCategory::ThreeOfAKind),
+            (["7♠", "8♠", "9♠"], ThreeCardCategory::StraightFlush),
+        ];
+        for (names, category) in cases {
+            assert_eq!(
+                three_card_value(&deck, &names).category,
+                category,
+                "{names:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn three_card_comparisons() {
+        let deck = Deck::new();
+        let value = |names| three_card_value(&deck, names);
+        assert!(value(&["Q♠", "6♦", "4♣"]) > value(&["Q♠", "5♦", "4♣"]));
+        assert!(value(&["A♠", "6♦", "4♣"]) > value(&["K♠", "Q♦", "T♣"]));
+        assert!(value(&["2♠", "2♦", "3♣"]) > value(&["A♠", "K♦", "J♣"]));
+        assert!(value(&["5♠", "5♦", "3♣"]) > value(&["4♠", "4♦", "A♣"]));
+        assert!(value(&["5♠", "5♦", "A♣"]) > value(&["5♠", "5♦", "K♣"]));
+        assert!(value(&["2♠", "3♦", "4♣"]) > value(&["A♠", "2♦", "3♣"]));
+        assert!(value(&["A♠", "2♦", "3♣"]) > value(&["A♠", "K♠", "3♠"]));
+        assert_eq!(value(&["A♠", "2♦", "3♣"]), value(&["A♦", "2♣", "3♣"]));
+    }
+
+    /// Every player hand faces C(49, 3) dealer hands, and the win and loss
+    /// counts are symmetric over all pairs of hands.
+    #[test]
+    fn showdown_counts_are_consistent() {
+        let mut total_player_wins = 0;
+        let mut total_dealer_wins = 0;
+        let mut num_hands = 0;
+        for_each_showdown::<3, _, _, _>(
+            |hand| ThreeCardValue::from(hand),
+            ThreeCardValue::from(&Deck::new().draw_hand(&["2♠", "3♦", "5♣"]).unwrap()),
+            |_, _, counts| {
+                assert_eq!(counts.total(), 18424);
+                assert_eq!(counts.not_qualified, 0);
+                total_player_wins += counts.player_wins;
+                total_dealer_wins += counts.dealer_wins;
+                num_hands += 1;
+            },
+        );
+        assert_eq!(num_hands, 22100);
+        assert_eq!(total_player_wins, total_dealer_wins);
+    }
+
+    /// Published values: 3.37% for the ante and play bets (playing queen-six-four
+    /// or better), and 2.32% for the 1-4-6-30-40 pair plus table.
+    #[test]
+    fn three_card_poker() {
+        let edge = three_card_poker_house_edge(&ThreeCardPokerPayTable::default());
+        assert!((edge.ante_play.house_edge - 0.0337).abs() < 0.0001);
+        assert!((edge.pair_plus - 0.0232).abs() < 0.0001);
+        assert!(edge.ante_play.play_rate > 0.67 && edge.ante_play.play_rate < 0.68);
+    }
+
+    #[test]
+    fn caribbean_stud() {
+        let edge = caribbean_stud_house_edge(&CaribbeanStudPayTable::default());
+        assert!(edge.house_edge > 0.052 && edge.house_edge < 0.055);
+    }
+}
@@ -0,0 +1,418 @@
+use crate::card::{Card, Rank, Suit};
+use crate::card_subsets::{binomial, CardSubsets};
+use crate::deck::remaining_cards;
+use crate::draw_poker::{Holds, DRAW_HAND_SIZE};
+use crate::error::{PokerError, Result};
+use crate::hand::{check_unique, Hand};
+use crate::hand_score::{is_flush, is_straight};
+use crate::hand_stats::HandStats;
+use crate::parallel::prelude::*;
+
+/// Pays out a five card video poker hand, in coins won per coin bet. A
+/// payout of zero means that the bet is lost.
+pub trait PayTable: Sync {
+    fn payout(&self, hand: &Hand<DRAW_HAND_SIZE>) -> u32;
+}
+
+/// Pay table for Jacks or Better. The default is the "9/6" full-pay machine
+/// (named for the full house and flush payouts), with the royal flush paying
+/// 800 for one, which is what it pays per coin when playing max coins.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct JacksOrBetter {
+    pub royal_flush: u32,
+    pub straight_flush: u32,
+    pub four_of_a_kind: u32,
+    pub full_house: u32,
+    pub flush: u32,
+    pub straight: u32,
+    pub three_of_a_kind: u32,
+    pub two_pair: u32,
+    pub jacks_or_better: u32,
+}
+
+impl Default for JacksOrBetter {
+    fn default() -> Self {
+        JacksOrBetter {
+            royal_flush: 800,
+            straight_flush: 50,
+            four_of_a_kind: 25,
+            full_house: 9,
+            flush: 6,
+            straight: 4,
+            three_of_a_kind: 3,
+            two_pair: 2,
+            jacks_or_better: 1,
+        }
+    }
+}
+
+/// Pay table for Deuces Wild, where every two can stand in for any card.
+/// The default is the full-pay machine.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DeucesWild {
+    pub natural_royal_flush: u32,
+    pub four_deuces: u32,
+    pub wild_royal_flush: u32,
+    pub five_of_a_kind: u32,
+    pub straight_flush: u32,
+    pub four_of_a_kind: u32,
+    pub full_house: u32,
+    pub flush: u32,
+    pub straight: u32,
+    pub three_of_a_kind: u32,
+}
+
+impl Default for DeucesWild {
+    fn default() -> Self {
+        DeucesWild {
+            natural_royal_flush: 800,
+            four_deuces: 200,
+            wild_royal_flush: 25,
+            five_of_a_kind: 15,
+            straight_flush: 9,
+            four_of_a_kind: 5,
+            full_house: 3,
+            flush: 2,
+            straight: 2,
+            three_of_a_kind: 1,
+        }
+    }
+}
+
+/// Returns true if every rank with a non-zero count is in the ranks from
+/// ten through ace, which makes up a royal flush.
+fn only_broadway_ranks(rank_count: &[usize; Rank::NUM_RANKS]) -> bool {
+    (0..Rank::NUM_RANKS)
+        .all(|id| rank_count[id] == 0 || id == Rank::Ace.index() || id >= Rank::Ten.index())
+}
+
+impl PayTable for JacksOrBetter {
+    fn payout(&self, hand: &Hand<DRAW_HAND_SIZE>) -> u32 {
+        let stats = HandStats::from(hand);
+        let flush = is_flush(&stats);
+        let straight = is_straight(&stats);
+        if flush && straight {
+            if only_broadway_ranks(&stats.rank_count) {
+                return self.royal_flush;
+            }
+            return self.straight_flush;
+        }
+        let count_of = |n| stats.rank_count.iter().filter(|c| **c == n).count();
+        if count_of(4) == 1 {
+            self.four_of_a_kind
+        } else if count_of(3) == 1 && count_of(2) == 1 {
+            self.full_house
+        } else if flush {
+            self.flush
+        } else if straight {
+            self.straight
+        } else if count_of(3) == 1 {
+            self.three_of_a_kind
+        } else if count_of(2) == 2 {
+            self.two_pair
+        } else if [Rank::Ace, Rank::Jack, Rank::Queen, Rank::King]
+            .iter()
+            .any(|rank| stats.rank_count[rank.index()] == 2)
+        {
+            self.jacks_or_better
+        } else {
+            0
+        }
+    }
+}
+
+impl PayTable for DeucesWild {
+    fn payout(&self, hand: &Hand<DRAW_HAND_SIZE>) -> u32 {
+        let stats = HandStats::from(hand);
+        let deuce = Rank::Two.index();
+        let num_wild = stats.rank_count[deuce];
+        let mut natural_ranks = stats.rank_count;
+        natural_ranks[deuce] = 0;
+        let num_natural = DRAW_HAND_SIZE - num_wild;
+        if num_wild == 4 {
+            return self.four_deuces;
+        }
+
+        let flush = (0..Suit::NUM_SUITS)
+            .any(|suit| stats.suit_count[suit] - stats.card_count[deuce][suit] == num_natural);
+        let distinct = natural_ranks.iter().all(|c| *c <= 1);
+        // The natural cards must all fit within five consecutive ranks, where
+        // the ace can be low (A-5) or high (T-A).
+        let straight = distinct
+            && (0..=Rank::NUM_RANKS - 4).any(|low| {
+                (0..Rank::NUM_RANKS).all(|id| {
+                    let ace = Rank::Ace.index();
+                    let position = if id == ace && low > ace {
+                        Rank::NUM_RANKS
+                    } else {
+                        id
+                    };
+                    natural_ranks[id] == 0 || (low..low + 5).contains(&position)
+                })
+            });
+        let broadway = only_broadway_ranks(&natural_ranks);
+        let most_of_a_kind = natural_ranks.iter().max().unwrap() + num_wild;
+        let num_pairs = natural_ranks.iter().filter(|c| **c == 2).count();
+
+        if flush && straight && broadway {
+            if num_wild == 0 {
+                self.natural_royal_flush
+            } else {
+                self.wild_royal_flush
+            }
+        } else if most_of_a_kind == 5 {
+            self.five_of_a_kind
+        } else if flush && straight {
+            self.straight_flush
+        } else if most_of_a_kind == 4 {
+            self.four_of_a_kind
+        } else if (num_wild == 0 && most_of_a_kind == 3 && num_pairs == 1)
+            || (num_wild == 1 && num_pairs == 2)
+        {
+            self.full_house
+        } else if flush {
+            self.flush
+        } else if straight {
+            self.straight
+        } else if most_of_a_kind == 3 {
+            self.three_of_a_kind
+        } else {
+            0
+        }
+    }
+}
+
+/// Expected payout of one way to play a dealt hand.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HoldAnalysis {
+    pub holds: Holds,
+    pub expected_payout: f64,
+}
+
+/// Converts between a bit mask over the five dealt cards and the holds.
+fn holds_from_mask(mask: usize) -> Holds {
+    array_init::array_init(|i| mask & (1 << i) != 0)
+}
+
+/// Calls `func` with every way to fill `buffer[index..]` from `stub[first..]`,
+/// keeping the stub order.
+fn for_each_draw<F>(
+    stub: &[Card],
+    first: usize,
+    buffer: &mut Hand<DRAW_HAND_SIZE>,
+    index: usize,
+    func: &mut F,
+) where
+    F: FnMut(&Hand<DRAW_HAND_SIZE>),
+{
+    if index == DRAW_HAND_SIZE {
+        func(buffer);
+        return;
+    }
+    for i in first..stub.len() {
+        buffer.cards[index] = stub[i];
+        for_each_draw(stub, i + 1, buffer, index + 1, func);
+    }
+}
+
+/// Expected payout of each of the 32 ways to play `hand`, found by drawing
+/// every possible set of replacement cards from the other 47. The results are
+/// indexed by a bit mask of the held cards. Fails if the hand has the same
+/// card twice.
+pub fn analyze_holds<P: PayTable>(
+    pay_table: &P,
+    hand: &Hand<DRAW_HAND_SIZE>,
+) -> Result<Vec<HoldAnalysis>> {
+    check_unique(&hand.cards)?;
+    let stub = remaining_cards(&hand.cards);
+    Ok((0..1 << DRAW_HAND_SIZE)
+        .map(|mask| {
+            let holds = holds_from_mask(mask);
+            let mut buffer = Hand { cards: hand.cards };
+            // Move the held cards to the front, then draw the rest.
+            let mut num_held = 0;
+            for (card, held) in hand.cards.iter().zip(holds) {
+                if held {
+                    buffer.cards[num_held] = *card;
+                    num_held += 1;
+                }
+            }
+            let mut total_payout = 0;
+            for_each_draw(&stub, 0, &mut buffer, num_held, &mut |draw| {
+                total_payout += pay_table.payout(draw) as u64;
+            });
+            let num_draws = binomial(stub.len(), DRAW_HAND_SIZE - num_held);
+            HoldAnalysis {
+                holds,
+                expected_payout: total_payout as f64 / num_draws as f64,
+            }
+        })
+        .collect())
+}
+
+/// The hold with the largest expected payout. Ties go to the hold that keeps
+/// the most cards, which is how the machine would usually be played. Fails if
+/// there are no holds to choose from.
+pub fn best_hold(analysis: &[HoldAnalysis]) -> Result<HoldAnalysis> {
+    analysis
+        .iter()
+        .max_by(|a, b| {
+            let held = |h: &HoldAnalysis| h.holds.iter().filter(|x| **x).count();
+            a.expected_payout
+                .total_cmp(&b.expected_payout)
+                .then(held(a).cmp(&held(b)))
+        })
+        .copied()
+        .ok_or(PokerError::Zero("holds"))
+}
+
+/// Total payout of every five card hand that contains a given set of cards,
+/// for every set of zero to five cards. This turns the expected payout of a
+/// hold into a handful of lookups instead of up to 1.5 million evaluations,
+/// which is what makes it practical to play out all C(52, 5) deals.
+fn payout_sums<P: PayTable>(pay_table: &P) -> CardSubsets {
+    let mut payout_sums = CardSubsets::new(DRAW_HAND_SIZE);
+    let stub = remaining_cards(&[]);
+    let mut buffer = Hand {
+        cards: [Card::ALL[0]; DRAW_HAND_SIZE],
+    };
+    for_each_draw(&stub, 0, &mut buffer, 0, &mut |hand| {
+        payout_sums.add_to_subsets(&hand.cards, pay_table.payout(hand) as i64);
+    });
+    payout_sums
+}
+
+/// Expected payout of the best hold for `hand`, which must be sorted by card
+/// id. The final hands for a hold contain the held cards, but none of the
+/// discarded ones (they are not in the deck any more).
+fn best_expected_payout(payout_sums: &CardSubsets, hand: &Hand<DRAW_HAND_SIZE>) -> f64 {
+    let num_stub_cards = Card::NUM_CARDS - DRAW_HAND_SIZE;
+    let subset_totals = payout_sums.subset_totals(&hand.cards);
+    (0..1 << DRAW_HAND_SIZE)
+        .map(|held: usize| {
+            let total = CardSubsets::inclusion_exclusion(&subset_totals, held);
+            let num_discarded = DRAW_HAND_SIZE - held.count_ones() as usize;
+            total as f64 / binomial(num_stub_cards, num_discarded) as f64
+        })
+        .fold(0.0, f64::max)
+}
+
+/// Return to player of a machine with perfect play: the expected payout of
+/// the best hold, averaged over every one of the C(52, 5) possible deals.
+/// This takes a few seconds (in release mode), most of it spent scoring every
+/// five card hand once.
+pub fn return_to_player<P: PayTable>(pay_table: &P) -> f64 {
+    let payout_sums = payout_sums(pay_table);
+    let total: f64 = (0..Card::NUM_CARDS)
+        .into_par_iter()
+        .map(|first_id| {
+            let stub: Vec<Card> = Card::ALL[first_id + 1..].to_vec();
+            let mut buffer = Hand {
+                cards: [Card::ALL[first_id]; DRAW_HAND_SIZE],
+            };
+            let mut total = 0.0;
+            for_each_draw(&stub, 0, &mut buffer, 1, &mut |hand| {
+                total += best_expected_payout(&payout_sums, hand);
+            });
+            total
+        })
+        .sum();
+    total / binomial(Card::NUM_CARDS, DRAW_HAND_SIZE) as f64
+}
+
+//////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use crate::deck::Deck;
+    use crate::video_poker::analyze_holds;
+    use crate::video_poker::best_hold;
+    use crate::video_poker::return_to_player;
+    use crate::video_poker::DeucesWild;
+    use crate::video_poker::JacksOrBetter;
+    use crate::video_poker::PayTable;
+
+    #[test]
+    fn jacks_or_better_payouts() {
+        let deck = Deck::new();
+        let pay_table = JacksOrBetter::default();
+        let cases = [
+            (["T♠", "J♠", "Q♠", "K♠", "A♠"], 800),
+            (["9♠", "T♠", "J♠", "Q♠", "K♠"], 50),
+            (["A♠", "2♠", "3♠", "4♠", "5♠"], 50),
+            (["A♠", "A♦", "A♣", "A♥", "5♠"], 25),
+            (["T♠", "J♦", "Q♠", "K♠", "A♠"], 4),
+            (["J♠", "J♦", "2♠", "3♥", "9♠"], 1),
+            (["T♠", "T♦", "2♠", "3♥", "9♠"], 0),
+            (["T♠", "T♦", "2♠", "2♥", "9♠"], 2),
+        ];
+        for (names, payout) in cases {
+            let hand = deck.draw_hand(&names).unwrap();
+            assert_eq!(pay_table.payout(&hand), payout, "{names:?}");
+        }
+    }
+
+    #[test]
+    fn deuces_wild_payouts() {
+        let deck = Deck::new();
+        let pay_table = DeucesWild::default();
+        let cases = [
+            (["T♠", "J♠", "Q♠", "K♠", "A♠"], 800),
+            (["2♠", "2♦", "2♣", "2♥", "5♠"], 200),
+            (["T♠", "J♠", "2♦", "K♠", "A♠"], 25),
+            (["7♠", "7♦", "2♣", "2♥", "7♥"], 15),
+            (["A♠", "2♦", "3♠", "4♠", "5♠"], 9),
+            (["7♠", "7♦", "2♣", "9♥", "7♥"], 5),
+            (["7♠", "7♦", "2♣", "9♥", "9♠"], 3),
+            (["7♠", "K♠", "2♣", "9♠", "3♠"], 2),
+            (["T♦", "J♠", "2♦", "K♠", "A♠"], 2),
+            (["7♠", "4♦", "2♣", "9♥", "7♥"], 1),
+            (["7♠", "4♦", "5♣", "9♥", "7♥"], 0),
+        ];
+        for (names, payout) in cases {
+            let hand = deck.draw_hand(&names).unwrap();
+            assert_eq!(pay_table.payout(&hand), payout, "{names:?}");
+        }
+    }
+
+    #[test]
+    fn best_holds() {
+        let deck = Deck::new();
+        let pay_table = JacksOrBetter::default();
+
+        // A dealt royal flush is always kept, and always pays.
+        let royal = deck.draw_hand(&["T♠", "J♠", "Q♠", "K♠", "A♠"]).unwrap();
+        let analysis = analyze_holds(&pay_table, &royal).unwrap();
+        assert_eq!(analysis.len(), 32);
+        let best = best_hold(&analysis).unwrap();
+        assert_eq!(best.holds, [true; 5]);
+        assert_eq!(best.expected_payout, 800.0);
+
+        // Break up a flush to draw to the royal.
+        let four_to_royal = deck.draw_hand(&["T♥", "J♥", "Q♥", "K♥", "3♥"]).unwrap();
+        let best = best_hold(&analyze_holds(&pay_table, &four_to_royal).unwrap()).unwrap();
+        assert_eq!(best.holds, [true, true, true, true, false]);
+
+        // Keep a high pair and nothing else.
+        let high_pair = deck.draw_hand(&["Q♦", "4♣", "Q♥", "8♠", "2♥"]).unwrap();
+        let best = best_hold(&analyze_holds(&pay_table, &high_pair).unwrap()).unwrap();
+        assert_eq!(best.holds, [true, false, true, false, false]);
+        assert!(best.expected_payout > 1.5 && best.expected_payout < 1.6);
+
+        let duplicate = deck.draw_hand(&["Q♦", "4♣", "Q♦", "8♠", "2♥"]).unwrap();
+        assert!(analyze_holds(&pay_table, &duplicate).is_err());
+        assert!(best_hold(&[]).is_err());
+    }
+
+    /// The published returns of the full-pay machines. This plays every deal,
+    /// which takes a few seconds in release mode, so run it with
+    /// `cargo test --release -- --ignored`.
+    #[test]
+    #[ignore]
+    fn full_pay_return_to_player() {
+        let rtp = return_to_player(&JacksOrBetter::default());
+        assert!((rtp - 0.995439).abs() < 1e-4, "{rtp}");
+        let rtp = return_to_player(&DeucesWild::default());
+        assert!((rtp - 1.007620).abs() < 1e-4, "{rtp}");
+    }
+}